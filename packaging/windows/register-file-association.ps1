@@ -0,0 +1,28 @@
+# Adds Firefox Session Data Utility to the "Open with" menu of Firefox session
+# files for the current user. Pass the path of the executable if it isn't in
+# the same folder as this script:
+#
+#     .\register-file-association.ps1 -Executable C:\path\to\firefox-session-ui-gpui.exe
+param(
+    [string]$Executable = (Join-Path $PSScriptRoot 'firefox-session-ui-gpui.exe')
+)
+
+$Executable = (Resolve-Path $Executable -ErrorAction Stop).Path
+$progId = 'FirefoxSessionUiGpui.SessionFile'
+
+# CreateSubKey opens existing keys without removing values that other programs
+# added to them:
+function Set-ClassesValue($key, $name, $value) {
+    $subKey = [Microsoft.Win32.Registry]::CurrentUser.CreateSubKey("Software\Classes\$key")
+    $subKey.SetValue($name, $value)
+    $subKey.Close()
+}
+
+Set-ClassesValue $progId '' 'Firefox session file'
+Set-ClassesValue "$progId\DefaultIcon" '' "`"$Executable`",0"
+Set-ClassesValue "$progId\shell\open\command" '' "`"$Executable`" `"%1`""
+foreach ($extension in '.jsonlz4', '.baklz4') {
+    Set-ClassesValue "$extension\OpenWithProgids" $progId ''
+}
+
+Write-Host "Registered $Executable for .jsonlz4 and .baklz4 files."
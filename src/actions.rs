@@ -0,0 +1,112 @@
+//! Keyboard actions and their default key bindings.
+
+use gpui::{actions, App, KeyBinding};
+
+/// Key context of the main view.
+pub const MAIN_CONTEXT: &str = "FirefoxSessionUtility";
+/// Key context of the view that shows session tabs.
+pub const WORKSPACE_CONTEXT: &str = "Workspace";
+/// Key context of the sidebar that lists windows.
+pub const SIDEBAR_CONTEXT: &str = "TabGroupList";
+/// The sidebar without the text inputs in it, so that spaces can still be
+/// typed into the search and filter boxes.
+const SIDEBAR_LIST_CONTEXT: &str = "TabGroupList && !Input";
+
+actions!(
+    firefox_session,
+    [
+        /// Prompt for a sessionstore file to load.
+        BrowseInput,
+        /// Load the file in the input path.
+        LoadInput,
+        /// Load the currently loaded file again.
+        ReloadInput,
+        /// Copy the generated links to the clipboard.
+        CopyLinks,
+        /// Prompt for a file path to save links to.
+        BrowseOutput,
+        /// Save links to the output path.
+        SaveLinks,
+        /// Show a preview of the generated PDF file.
+        PreviewPdf,
+        /// Select or deselect the focused window in the sidebar.
+        ToggleWindowSelection,
+        /// Select every open and closed window.
+        SelectAllWindows,
+        /// Deselect every window.
+        SelectNoWindows,
+        /// Invert the selection of windows and tabs.
+        InvertSelection,
+        /// Select the next output format.
+        NextOutputFormat,
+        /// Select the previous output format.
+        PreviousOutputFormat,
+        /// Show or hide the output options.
+        ToggleOutputOptions,
+        /// Open the selected tabs in a browser.
+        OpenInBrowser,
+        /// Search all tabs in the loaded session.
+        SearchTabs,
+        /// Open the settings.
+        OpenSettings,
+        /// Show or hide the log of earlier status messages.
+        ToggleStatusLog,
+        /// Show or hide the inspector that lists handled messages.
+        ToggleInspector,
+        /// Undo the latest change to the selection or output options.
+        Undo,
+        /// Redo the latest change that was undone.
+        Redo,
+        /// Switch between the system, light and dark themes.
+        CycleTheme,
+        /// Make text larger.
+        IncreaseFontSize,
+        /// Make text smaller.
+        DecreaseFontSize,
+        /// Use the default text size.
+        ResetFontSize,
+        /// Open a new session tab.
+        NewSessionTab,
+        /// Close the current session tab.
+        CloseSessionTab,
+        /// Switch to the next session tab.
+        NextSessionTab,
+    ]
+);
+
+/// Register the default key bindings.
+pub fn bind_keys(cx: &mut App) {
+    let main = Some(MAIN_CONTEXT);
+    cx.bind_keys([
+        KeyBinding::new("secondary-o", BrowseInput, main),
+        KeyBinding::new("secondary-enter", LoadInput, main),
+        KeyBinding::new("f5", ReloadInput, main),
+        KeyBinding::new("secondary-r", ReloadInput, main),
+        KeyBinding::new("secondary-shift-c", CopyLinks, main),
+        KeyBinding::new("secondary-shift-s", BrowseOutput, main),
+        KeyBinding::new("secondary-s", SaveLinks, main),
+        KeyBinding::new("secondary-p", PreviewPdf, main),
+        KeyBinding::new("space", ToggleWindowSelection, Some(SIDEBAR_LIST_CONTEXT)),
+        KeyBinding::new("secondary-a", SelectAllWindows, main),
+        KeyBinding::new("secondary-shift-a", SelectNoWindows, main),
+        KeyBinding::new("secondary-i", InvertSelection, main),
+        KeyBinding::new("secondary-]", NextOutputFormat, main),
+        KeyBinding::new("secondary-[", PreviousOutputFormat, main),
+        KeyBinding::new("secondary-shift-o", ToggleOutputOptions, main),
+        KeyBinding::new("secondary-shift-enter", OpenInBrowser, main),
+        KeyBinding::new("secondary-shift-f", SearchTabs, main),
+        KeyBinding::new("secondary-,", OpenSettings, main),
+        KeyBinding::new("secondary-j", ToggleStatusLog, main),
+        KeyBinding::new("secondary-shift-i", ToggleInspector, main),
+        KeyBinding::new("secondary-z", Undo, main),
+        KeyBinding::new("secondary-shift-z", Redo, main),
+        KeyBinding::new("secondary-y", Redo, main),
+        KeyBinding::new("secondary-=", IncreaseFontSize, main),
+        KeyBinding::new("secondary-+", IncreaseFontSize, main),
+        KeyBinding::new("secondary--", DecreaseFontSize, main),
+        KeyBinding::new("secondary-0", ResetFontSize, main),
+        KeyBinding::new("secondary-t", NewSessionTab, Some(WORKSPACE_CONTEXT)),
+        KeyBinding::new("secondary-w", CloseSessionTab, Some(WORKSPACE_CONTEXT)),
+        KeyBinding::new("ctrl-tab", NextSessionTab, Some(WORKSPACE_CONTEXT)),
+    ]);
+}
@@ -1,9 +1,303 @@
 //! Utilities for using ELM like architecture where UI updates are done in response to messages.
+//!
+//! A view handles messages in [`Update::update`], which changes state and
+//! returns an [`Effect`] that describes work that should happen afterwards,
+//! such as background tasks, messages that should be sent later or changes to
+//! the window. Effects are executed by [`Update::dispatch`], so `update` itself
+//! never starts any tasks and doesn't have access to the window.
+//!
+//! Long-lived sources of messages such as timers and file watchers are
+//! started with [`Effect::subscribe`], see the [`subscription`] module. Handled
+//! messages can be recorded and replayed using the [`trace`] module.
 
-use gpui::{AsyncWindowContext, Context, WeakEntity, Window};
+pub mod subscription;
+pub mod trace;
 
-pub trait Update<M>: Sized {
-    fn update(&mut self, window: &mut Window, cx: &mut Context<Self>, msg: M);
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::time::Duration;
+
+use gpui::{App, AsyncWindowContext, Context, EntityId, Global, Task, WeakEntity, Window};
+
+use self::subscription::Sub;
+use self::trace::Source;
+
+/// A message that can be handled by an [`Update`] implementor.
+pub trait Message: fmt::Debug + 'static {
+    /// Short description of the message that is shown in the inspector.
+    fn describe(&self) -> String {
+        format!("{self:?}")
+    }
+
+    /// The message as JSON, stored when it is recorded so that it can be
+    /// replayed by [`trace::replay`]. `None` if the message can't be replayed.
+    fn to_json(&self) -> Option<serde_json::Value> {
+        None
+    }
+}
+
+pub trait Update<M: Message>: Sized + 'static {
+    /// Change state in response to a message and return the side effects that
+    /// should run afterwards. Anything that needs the window, such as opening a
+    /// modal, is done with [`Effect::window`].
+    fn update(&mut self, cx: &mut Context<Self>, msg: M) -> Effect<M>;
+
+    /// Short description of the current state. Recorded after each message
+    /// while messages are being traced.
+    fn summary(&self, _cx: &App) -> String {
+        String::new()
+    }
+
+    /// Handle a message and run the effects that it produced.
+    fn dispatch(&mut self, window: &mut Window, cx: &mut Context<Self>, msg: M) {
+        handle(self, window, cx, msg, Source::View);
+    }
+}
+
+/// Handle a message, record it if tracing is enabled and then run its effects.
+fn handle<T, M>(view: &mut T, window: &mut Window, cx: &mut Context<T>, msg: M, source: Source)
+where
+    T: Update<M>,
+    M: Message,
+{
+    let entry = trace::begin(cx, source, &msg);
+    let effect = view.update(cx, msg);
+    if let Some(mut entry) = entry {
+        entry.state = view.summary(cx);
+        let id = cx.entity_id();
+        trace::record(cx, id, entry);
+        // Show the new entry in the inspector:
+        cx.notify();
+    }
+    run_effect(view, window, cx, effect);
+}
+
+/// Identifies a spawned task so that it can be cancelled. Spawning a task with
+/// the same id as a running task cancels the old task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TaskId(pub &'static str);
+
+type SpawnFn<M> = Box<dyn FnOnce(MsgSink<M>) -> Pin<Box<dyn Future<Output = ()>>>>;
+type WindowFn<M> = Box<dyn FnOnce(&mut Window, &mut App) -> Effect<M>>;
+
+/// Work that should be done after a message was handled.
+#[must_use = "effects do nothing unless they are returned from `Update::update`"]
+pub enum Effect<M> {
+    None,
+    /// Handle another message right away.
+    Send(M),
+    /// Run several effects in order.
+    Batch(Vec<Effect<M>>),
+    /// Run a future that can send any number of messages.
+    Spawn {
+        id: Option<TaskId>,
+        task: SpawnFn<M>,
+    },
+    /// Send a message after a delay.
+    SendLater {
+        id: Option<TaskId>,
+        delay: Duration,
+        msg: M,
+    },
+    /// Start a subscription that sends messages until it is cancelled or the
+    /// view is dropped.
+    Subscribe {
+        id: Option<TaskId>,
+        sub: Sub<M>,
+    },
+    /// Stop a task or subscription that was started with an id.
+    Cancel(TaskId),
+    /// Use the view's window and then run the effect that was returned.
+    Window(WindowFn<M>),
+}
+impl<M: Message> Effect<M> {
+    pub fn none() -> Self {
+        Self::None
+    }
+    pub fn send(msg: M) -> Self {
+        Self::Send(msg)
+    }
+    pub fn batch(effects: impl IntoIterator<Item = Self>) -> Self {
+        Self::Batch(effects.into_iter().collect())
+    }
+    /// Run a future that is given a [`MsgSink`] to send messages with.
+    pub fn spawn<F, Fut>(f: F) -> Self
+    where
+        F: FnOnce(MsgSink<M>) -> Fut + 'static,
+        Fut: Future<Output = ()> + 'static,
+    {
+        Self::Spawn {
+            id: None,
+            task: Box::new(move |sink| Box::pin(f(sink))),
+        }
+    }
+    /// Run a future and send the message that it returns.
+    pub fn perform(future: impl Future<Output = M> + 'static) -> Self {
+        Self::spawn(|mut sink| async move {
+            sink.send(future.await);
+        })
+    }
+    pub fn send_later(delay: Duration, msg: M) -> Self {
+        Self::SendLater {
+            id: None,
+            delay,
+            msg,
+        }
+    }
+    pub fn subscribe(sub: Sub<M>) -> Self {
+        Self::Subscribe { id: None, sub }
+    }
+    pub fn cancel(id: TaskId) -> Self {
+        Self::Cancel(id)
+    }
+    /// Use the window, for example to open a modal or to change the text of
+    /// an input. The view is still being updated when `f` runs, so `f` must
+    /// not update it.
+    pub fn window(f: impl FnOnce(&mut Window, &mut App) + 'static) -> Self {
+        Self::with_window(|window, cx| {
+            f(window, cx);
+            Self::None
+        })
+    }
+    /// Use the window and then run the effect that `f` returns, for example a
+    /// task that waits for a file prompt that needs the window as its parent.
+    pub fn with_window(f: impl FnOnce(&mut Window, &mut App) -> Self + 'static) -> Self {
+        Self::Window(Box::new(f))
+    }
+    /// Give a spawned task or a subscription an id so that it can be
+    /// cancelled, and so that it replaces earlier tasks with the same id.
+    pub fn with_id(self, task_id: TaskId) -> Self {
+        match self {
+            Self::Spawn { task, .. } => Self::Spawn {
+                id: Some(task_id),
+                task,
+            },
+            Self::SendLater { delay, msg, .. } => Self::SendLater {
+                id: Some(task_id),
+                delay,
+                msg,
+            },
+            Self::Subscribe { sub, .. } => Self::Subscribe {
+                id: Some(task_id),
+                sub,
+            },
+            other => other,
+        }
+    }
+}
+
+/// Tasks and subscriptions that were started with an id for each view, so
+/// that they can be cancelled. They are dropped when their view is released.
+#[derive(Default)]
+struct RunningTasks(HashMap<EntityId, HashMap<TaskId, Task<()>>>);
+impl Global for RunningTasks {}
+
+/// Remember a task so that it can be cancelled, replacing any earlier task
+/// with the same id.
+fn keep_task<T: 'static>(cx: &mut Context<T>, id: TaskId, task: Task<()>) {
+    let entity = cx.entity_id();
+    if !cx.default_global::<RunningTasks>().0.contains_key(&entity) {
+        // Dropping a task cancels it, so this stops everything the view
+        // started once it is gone:
+        cx.on_release(move |_, cx| {
+            cx.default_global::<RunningTasks>().0.remove(&entity);
+        })
+        .detach();
+    }
+    cx.default_global::<RunningTasks>()
+        .0
+        .entry(entity)
+        .or_default()
+        .insert(id, task);
+}
+
+/// Execute an effect on behalf of a view.
+fn run_effect<T, M>(view: &mut T, window: &mut Window, cx: &mut Context<T>, effect: Effect<M>)
+where
+    T: Update<M>,
+    M: Message,
+{
+    let task = match effect {
+        Effect::None => return,
+        Effect::Send(msg) => {
+            handle(view, window, cx, msg, Source::Effect);
+            return;
+        }
+        Effect::Batch(effects) => {
+            for effect in effects {
+                run_effect(view, window, cx, effect);
+            }
+            return;
+        }
+        Effect::Window(f) => {
+            let effect = f(window, cx);
+            run_effect(view, window, cx, effect);
+            return;
+        }
+        Effect::Cancel(id) => {
+            let entity = cx.entity_id();
+            if let Some(tasks) = cx.default_global::<RunningTasks>().0.get_mut(&entity) {
+                // Dropping a task cancels it:
+                tasks.remove(&id);
+            }
+            return;
+        }
+        Effect::Spawn { id, task } => {
+            let task = MsgSender::from_cx(window, cx)
+                .spawn(async move |_window, sender| task(MsgSink::new(sender, Source::Task)).await);
+            (id, task)
+        }
+        Effect::Subscribe { id, sub } => {
+            let executor = cx.background_executor().clone();
+            let task = MsgSender::from_cx(window, cx).spawn(async move |_window, sender| {
+                sub.run(MsgSink::new(sender, Source::Subscription), executor)
+                    .await
+            });
+            (id, task)
+        }
+        Effect::SendLater { id, delay, msg } => {
+            let timer = cx.background_executor().timer(delay);
+            let task = MsgSender::from_cx(window, cx).spawn(async move |_window, mut sender| {
+                timer.await;
+                sender.send_from(msg, Source::Task);
+            });
+            (id, task)
+        }
+    };
+    match task {
+        (Some(id), task) => keep_task(cx, id, task),
+        // Stops the next time it sends a message after the view was dropped:
+        (None, task) => task.detach(),
+    }
+}
+
+/// Sends messages from a spawned [`Effect`] to the view that returned it.
+pub struct MsgSink<M> {
+    send: Rc<dyn Fn(M) -> bool>,
+}
+impl<M: Message> MsgSink<M> {
+    fn new<T: Update<M>>(sender: MsgSender<T>, source: Source) -> Self {
+        Self {
+            send: Rc::new(move |msg| sender.clone().send_from(msg, source)),
+        }
+    }
+}
+impl<M> MsgSink<M> {
+    /// Returns `false` if the view was dropped, in which case there is no
+    /// point in sending more messages.
+    pub fn send(&mut self, msg: M) -> bool {
+        (self.send)(msg)
+    }
+}
+impl<M> Clone for MsgSink<M> {
+    fn clone(&self) -> Self {
+        Self {
+            send: self.send.clone(),
+        }
+    }
 }
 
 pub struct MsgSender<T> {
@@ -38,18 +332,29 @@ impl<T: 'static> MsgSender<T> {
     pub fn send<M>(&mut self, msg: M)
     where
         T: Update<M>,
+        M: Message,
+    {
+        self.send_from(msg, Source::View);
+    }
+
+    /// Returns `false` if the view or its window was closed.
+    fn send_from<M>(&mut self, msg: M, source: Source) -> bool
+    where
+        T: Update<M>,
+        M: Message,
     {
-        _ = self
-            .window_and_cx
+        self.window_and_cx
             .window_handle()
             .update(&mut self.window_and_cx, |_, window, cx| {
                 let Some(view) = self.weak.upgrade() else {
-                    return;
+                    return false;
                 };
-                _ = view.update(cx, |view, cx| {
-                    T::update(view, window, cx, msg);
+                view.update(cx, |view, cx| {
+                    handle(view, window, cx, msg, source);
                 });
-            });
+                true
+            })
+            .unwrap_or(false)
     }
 }
 impl<T> Clone for MsgSender<T> {
@@ -60,3 +365,176 @@ impl<T> Clone for MsgSender<T> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use gpui::{div, IntoElement, Render, TestAppContext, WindowHandle};
+
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum TestMsg {
+        Start,
+        Got(u32),
+        Cancel,
+    }
+    impl Message for TestMsg {}
+
+    const LATER_TASK: TaskId = TaskId("later");
+    const DELAY: Duration = Duration::from_millis(100);
+
+    /// Remembers the messages it handled.
+    struct TestView {
+        received: Vec<TestMsg>,
+        /// Returned when [`TestMsg::Start`] is handled.
+        on_start: Option<Effect<TestMsg>>,
+    }
+    impl Render for TestView {
+        fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+            div()
+        }
+    }
+    impl Update<TestMsg> for TestView {
+        fn update(&mut self, _cx: &mut Context<Self>, msg: TestMsg) -> Effect<TestMsg> {
+            let effect = match msg {
+                TestMsg::Start => self.on_start.take().unwrap_or_else(Effect::none),
+                TestMsg::Got(_) => Effect::none(),
+                TestMsg::Cancel => Effect::cancel(LATER_TASK),
+            };
+            self.received.push(msg);
+            effect
+        }
+    }
+
+    /// Open a view that runs `on_start` when it is sent [`TestMsg::Start`].
+    fn start(cx: &mut TestAppContext, on_start: Effect<TestMsg>) -> WindowHandle<TestView> {
+        let handle = cx.add_window(|_window, _cx| TestView {
+            received: Vec::new(),
+            on_start: Some(on_start),
+        });
+        send(cx, handle, TestMsg::Start);
+        handle
+    }
+
+    fn send(cx: &mut TestAppContext, handle: WindowHandle<TestView>, msg: TestMsg) {
+        handle
+            .update(cx, |view, window, cx| view.dispatch(window, cx, msg))
+            .unwrap();
+    }
+
+    fn received(cx: &mut TestAppContext, handle: WindowHandle<TestView>) -> Vec<TestMsg> {
+        handle
+            .update(cx, |view, _window, _cx| view.received.clone())
+            .unwrap()
+    }
+
+    #[gpui::test]
+    fn batch_runs_effects_in_order(cx: &mut TestAppContext) {
+        let effect = Effect::batch([
+            Effect::send(TestMsg::Got(1)),
+            Effect::none(),
+            Effect::batch([Effect::send(TestMsg::Got(2)), Effect::send(TestMsg::Got(3))]),
+        ]);
+        let handle = start(cx, effect);
+        assert_eq!(
+            received(cx, handle),
+            [
+                TestMsg::Start,
+                TestMsg::Got(1),
+                TestMsg::Got(2),
+                TestMsg::Got(3)
+            ]
+        );
+    }
+
+    #[gpui::test]
+    fn spawned_task_sends_messages(cx: &mut TestAppContext) {
+        let effect = Effect::spawn(|mut sink| async move {
+            sink.send(TestMsg::Got(1));
+            sink.send(TestMsg::Got(2));
+        });
+        let handle = start(cx, effect);
+        assert_eq!(received(cx, handle), [TestMsg::Start]);
+
+        cx.run_until_parked();
+        assert_eq!(
+            received(cx, handle),
+            [TestMsg::Start, TestMsg::Got(1), TestMsg::Got(2)]
+        );
+    }
+
+    #[gpui::test]
+    fn send_later_waits_for_delay(cx: &mut TestAppContext) {
+        let handle = start(cx, Effect::send_later(DELAY, TestMsg::Got(1)));
+        cx.run_until_parked();
+        assert_eq!(received(cx, handle), [TestMsg::Start]);
+
+        cx.executor().advance_clock(DELAY);
+        cx.run_until_parked();
+        assert_eq!(received(cx, handle), [TestMsg::Start, TestMsg::Got(1)]);
+    }
+
+    #[gpui::test]
+    fn cancel_stops_task_with_id(cx: &mut TestAppContext) {
+        let effect = Effect::send_later(DELAY, TestMsg::Got(1)).with_id(LATER_TASK);
+        let handle = start(cx, effect);
+        send(cx, handle, TestMsg::Cancel);
+
+        cx.executor().advance_clock(DELAY);
+        cx.run_until_parked();
+        assert_eq!(received(cx, handle), [TestMsg::Start, TestMsg::Cancel]);
+    }
+
+    #[gpui::test]
+    fn task_with_same_id_replaces_earlier_task(cx: &mut TestAppContext) {
+        let effect = Effect::batch([
+            Effect::send_later(DELAY, TestMsg::Got(1)).with_id(LATER_TASK),
+            Effect::send_later(DELAY, TestMsg::Got(2)).with_id(LATER_TASK),
+        ]);
+        let handle = start(cx, effect);
+
+        cx.executor().advance_clock(DELAY);
+        cx.run_until_parked();
+        assert_eq!(received(cx, handle), [TestMsg::Start, TestMsg::Got(2)]);
+    }
+
+    #[gpui::test]
+    fn subscription_runs_until_cancelled(cx: &mut TestAppContext) {
+        let effect = Effect::subscribe(Sub::every(DELAY, || TestMsg::Got(1))).with_id(LATER_TASK);
+        let handle = start(cx, effect);
+
+        for _ in 0..2 {
+            cx.executor().advance_clock(DELAY);
+            cx.run_until_parked();
+        }
+        send(cx, handle, TestMsg::Cancel);
+        cx.executor().advance_clock(DELAY);
+        cx.run_until_parked();
+        assert_eq!(
+            received(cx, handle),
+            [
+                TestMsg::Start,
+                TestMsg::Got(1),
+                TestMsg::Got(1),
+                TestMsg::Cancel
+            ]
+        );
+    }
+
+    #[gpui::test]
+    fn window_effect_runs_returned_effect(cx: &mut TestAppContext) {
+        let ran = Rc::new(Cell::new(false));
+        let effect = Effect::batch([
+            Effect::window({
+                let ran = ran.clone();
+                move |_window, _cx| ran.set(true)
+            }),
+            Effect::with_window(|_window, _cx| Effect::send(TestMsg::Got(1))),
+        ]);
+        let handle = start(cx, effect);
+        assert!(ran.get());
+        assert_eq!(received(cx, handle), [TestMsg::Start, TestMsg::Got(1)]);
+    }
+}
@@ -0,0 +1,125 @@
+//! Long-lived sources of messages such as timers, file watchers and channels
+//! that background threads can send values through. A subscription is started
+//! by returning [`Effect::subscribe`](super::Effect::subscribe) from
+//! [`Update::update`](super::Update::update) and runs until it is cancelled or
+//! the view is dropped.
+
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::time::Duration;
+
+use futures::channel::mpsc;
+use futures::StreamExt;
+use gpui::BackgroundExecutor;
+use notify::Watcher;
+
+use super::{Message, MsgSink};
+
+type RunFn<M> =
+    Box<dyn FnOnce(MsgSink<M>, BackgroundExecutor) -> Pin<Box<dyn Future<Output = ()>>>>;
+
+/// A long-lived source of messages for a view. Named after Elm's `Sub` to
+/// avoid confusion with [`gpui::Subscription`].
+pub struct Sub<M> {
+    run: RunFn<M>,
+}
+impl<M: Message> Sub<M> {
+    fn new<F, Fut>(f: F) -> Self
+    where
+        F: FnOnce(MsgSink<M>, BackgroundExecutor) -> Fut + 'static,
+        Fut: Future<Output = ()> + 'static,
+    {
+        Self {
+            run: Box::new(move |sink, executor| Box::pin(f(sink, executor))),
+        }
+    }
+
+    /// Send a message each time `interval` has passed.
+    #[cfg_attr(not(test), expect(dead_code))]
+    pub fn every(interval: Duration, mut msg: impl FnMut() -> M + 'static) -> Self {
+        Self::new(move |mut sink, executor| async move {
+            loop {
+                executor.timer(interval).await;
+                if !sink.send(msg()) {
+                    break;
+                }
+            }
+        })
+    }
+
+    /// Send a message for each value that is sent through the returned
+    /// channel, which can be used from any thread.
+    pub fn channel<T: 'static>(to_msg: impl Fn(T) -> M + 'static) -> (MsgChannel<T>, Self) {
+        let (sender, mut receiver) = mpsc::unbounded();
+        let sub = Self::new(move |mut sink, _executor| async move {
+            while let Some(value) = receiver.next().await {
+                if !sink.send(to_msg(value)) {
+                    break;
+                }
+            }
+        });
+        (MsgChannel(sender), sub)
+    }
+
+    /// Send a message when a file is created, changed or removed. The file's
+    /// folder is watched rather than the file itself, since Firefox replaces
+    /// session files by renaming a new file over them.
+    pub fn watch_file(path: PathBuf, msg: impl Fn() -> M + 'static) -> Result<Self, String> {
+        let folder = match path.parent() {
+            Some(folder) if !folder.as_os_str().is_empty() => folder.to_owned(),
+            _ => Path::new(".").to_owned(),
+        };
+        let (channel, sub) = Self::channel(move |()| msg());
+        // Events are reported on a background thread:
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let Ok(event) = event else {
+                    return;
+                };
+                let changed = !event.kind.is_access()
+                    && event
+                        .paths
+                        .iter()
+                        .any(|changed| changed.file_name() == path.file_name());
+                if changed {
+                    channel.send(());
+                }
+            })
+            .map_err(|e| e.to_string())?;
+        watcher
+            .watch(&folder, notify::RecursiveMode::NonRecursive)
+            .map_err(|e| e.to_string())?;
+        // Stop watching when the subscription stops:
+        Ok(sub.holding(watcher))
+    }
+
+    /// Keep a value alive for as long as the subscription runs.
+    fn holding(self, value: impl 'static) -> Self {
+        let run = self.run;
+        Self::new(move |sink, executor| {
+            let future = run(sink, executor);
+            async move {
+                let _value = value;
+                future.await;
+            }
+        })
+    }
+
+    pub(super) fn run(
+        self,
+        sink: MsgSink<M>,
+        executor: BackgroundExecutor,
+    ) -> Pin<Box<dyn Future<Output = ()>>> {
+        (self.run)(sink, executor)
+    }
+}
+
+/// Sends values to a [`Sub::channel`] from any thread.
+pub struct MsgChannel<T>(mpsc::UnboundedSender<T>);
+impl<T> MsgChannel<T> {
+    /// Returns `false` if the subscription has stopped.
+    pub fn send(&self, value: T) -> bool {
+        self.0.unbounded_send(value).is_ok()
+    }
+}
@@ -0,0 +1,267 @@
+//! Records every message that views handle, together with a summary of the
+//! view's state afterwards. Recordings are shown in the inspector, can be saved
+//! as JSON lines and replayed later to reproduce bugs that depend on the order
+//! of events.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use gpui::{App, EntityId, Global};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use super::{Effect, Message};
+
+/// Max number of entries that are kept for each view. Older entries are
+/// discarded first.
+const MAX_ENTRIES: usize = 10_000;
+
+/// Where a message came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Source {
+    /// Sent by the user interface.
+    View,
+    /// Returned as an [`Effect::Send`] while another message was handled.
+    Effect,
+    /// Sent by a spawned task or a timer.
+    Task,
+    /// Sent by a [`super::subscription::Sub`].
+    Subscription,
+}
+impl Source {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::View => "view",
+            Self::Effect => "effect",
+            Self::Task => "task",
+            Self::Subscription => "sub",
+        }
+    }
+}
+
+/// A recorded message.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceEntry {
+    /// Milliseconds since recording started.
+    pub elapsed_ms: u64,
+    pub source: Source,
+    pub description: String,
+    /// The serialized message, `None` if it can't be replayed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<serde_json::Value>,
+    /// Summary of the view's state after the message was handled.
+    pub state: String,
+}
+impl TraceEntry {
+    /// Time since recording started, shown in the inspector.
+    pub fn short_time(&self) -> String {
+        format!("{:.3}", self.elapsed_ms as f64 / 1000.)
+    }
+    /// The message that is sent when the recording is replayed. Only messages
+    /// sent by the user interface are replayed since effects, tasks and
+    /// subscriptions are started again by handling those messages.
+    fn replayed_message(&self) -> Option<&serde_json::Value> {
+        self.message
+            .as_ref()
+            .filter(|_| self.source == Source::View)
+    }
+}
+
+#[derive(Default)]
+struct Tracer {
+    /// When recording started, `None` if messages aren't being recorded.
+    started: Option<Instant>,
+    entries: HashMap<EntityId, VecDeque<TraceEntry>>,
+}
+impl Global for Tracer {}
+
+pub fn is_recording(cx: &App) -> bool {
+    cx.try_global::<Tracer>()
+        .is_some_and(|tracer| tracer.started.is_some())
+}
+
+/// Start recording messages for all views. Earlier recordings are discarded.
+pub fn start_recording(cx: &mut App) {
+    let tracer = cx.default_global::<Tracer>();
+    tracer.started = Some(Instant::now());
+    tracer.entries.clear();
+}
+
+/// Stop recording messages. Entries that were recorded are kept.
+pub fn stop_recording(cx: &mut App) {
+    cx.default_global::<Tracer>().started = None;
+}
+
+/// Messages that were recorded for a view, oldest first.
+pub fn entries(cx: &App, view: EntityId) -> &VecDeque<TraceEntry> {
+    static NO_ENTRIES: VecDeque<TraceEntry> = VecDeque::new();
+    cx.try_global::<Tracer>()
+        .and_then(|tracer| tracer.entries.get(&view))
+        .unwrap_or(&NO_ENTRIES)
+}
+
+pub fn clear(cx: &mut App, view: EntityId) {
+    cx.default_global::<Tracer>().entries.remove(&view);
+}
+
+/// Create an entry for a message if messages are being recorded. The state is
+/// filled in after the message was handled.
+pub(super) fn begin<M: Message>(cx: &App, source: Source, msg: &M) -> Option<TraceEntry> {
+    let started = cx.try_global::<Tracer>()?.started?;
+    Some(TraceEntry {
+        elapsed_ms: started.elapsed().as_millis() as u64,
+        source,
+        description: msg.describe(),
+        message: msg.to_json(),
+        state: String::new(),
+    })
+}
+
+pub(super) fn record(cx: &mut App, view: EntityId, entry: TraceEntry) {
+    let entries = cx
+        .default_global::<Tracer>()
+        .entries
+        .entry(view)
+        .or_default();
+    if entries.len() >= MAX_ENTRIES {
+        entries.pop_front();
+    }
+    entries.push_back(entry);
+}
+
+/// Write entries as JSON with one entry per line.
+pub fn to_json_lines<'a>(entries: impl IntoIterator<Item = &'a TraceEntry>) -> String {
+    let mut output = String::new();
+    for entry in entries {
+        if let Ok(line) = serde_json::to_string(entry) {
+            output.push_str(&line);
+            output.push('\n');
+        }
+    }
+    output
+}
+
+/// Read entries that were written by [`to_json_lines`].
+pub fn from_json_lines(text: &str) -> Result<Vec<TraceEntry>, String> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(ix, line)| serde_json::from_str(line).map_err(|e| format!("line {}: {e}", ix + 1)))
+        .collect()
+}
+
+/// Send the recorded messages from the user interface again with the same
+/// delays between them as when they were recorded.
+pub fn replay<M: Message + DeserializeOwned>(entries: &[TraceEntry]) -> Result<Effect<M>, String> {
+    let mut replayed = entries
+        .iter()
+        .filter_map(|entry| Some((entry.elapsed_ms, entry.replayed_message()?)))
+        .peekable();
+    let Some(&(start, _)) = replayed.peek() else {
+        return Ok(Effect::none());
+    };
+    let effects = replayed
+        .map(|(elapsed_ms, message)| {
+            let msg = M::deserialize(message).map_err(|e| e.to_string())?;
+            let delay = Duration::from_millis(elapsed_ms.saturating_sub(start));
+            Ok(Effect::send_later(delay, msg))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    Ok(Effect::batch(effects))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum TestMsg {
+        Click(u32),
+        Loaded,
+    }
+    impl Message for TestMsg {
+        fn to_json(&self) -> Option<serde_json::Value> {
+            serde_json::to_value(self).ok()
+        }
+    }
+
+    fn entry(elapsed_ms: u64, source: Source, msg: &TestMsg) -> TraceEntry {
+        TraceEntry {
+            elapsed_ms,
+            source,
+            description: msg.describe(),
+            message: msg.to_json(),
+            state: String::new(),
+        }
+    }
+
+    /// The messages and delays of the effects that a replay sends.
+    fn replayed(effect: Effect<TestMsg>) -> Vec<(Duration, TestMsg)> {
+        let Effect::Batch(effects) = effect else {
+            panic!("replay should return a batch");
+        };
+        effects
+            .into_iter()
+            .map(|effect| match effect {
+                Effect::SendLater {
+                    id: None,
+                    delay,
+                    msg,
+                } => (delay, msg),
+                _ => panic!("replay should only send messages later"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn record_and_replay_round_trip() {
+        let entries = vec![
+            entry(100, Source::View, &TestMsg::Click(1)),
+            entry(110, Source::Effect, &TestMsg::Loaded),
+            entry(150, Source::Task, &TestMsg::Loaded),
+            entry(200, Source::Subscription, &TestMsg::Loaded),
+            entry(350, Source::View, &TestMsg::Click(2)),
+        ];
+        let text = to_json_lines(&entries);
+        assert_eq!(text.lines().count(), entries.len());
+
+        let loaded = from_json_lines(&text).unwrap();
+        assert_eq!(loaded.len(), entries.len());
+        assert_eq!(loaded[1].source, Source::Effect);
+        assert_eq!(loaded[4].description, entries[4].description);
+
+        assert_eq!(
+            replayed(replay(&loaded).unwrap()),
+            vec![
+                (Duration::ZERO, TestMsg::Click(1)),
+                (Duration::from_millis(250), TestMsg::Click(2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn replay_skips_entries_without_messages() {
+        let mut skipped = entry(0, Source::View, &TestMsg::Click(1));
+        skipped.message = None;
+        let entries = [skipped, entry(40, Source::View, &TestMsg::Click(2))];
+        assert_eq!(
+            replayed(replay(&entries).unwrap()),
+            vec![(Duration::ZERO, TestMsg::Click(2))]
+        );
+    }
+
+    #[test]
+    fn replay_of_empty_recording_does_nothing() {
+        let entries = [entry(0, Source::Task, &TestMsg::Loaded)];
+        assert!(matches!(replay::<TestMsg>(&entries), Ok(Effect::None)));
+    }
+
+    #[test]
+    fn from_json_lines_reports_line_number() {
+        let text = format!(
+            "{}\n\nnot json\n",
+            to_json_lines(&[entry(0, Source::View, &TestMsg::Loaded)]).trim()
+        );
+        let error = from_json_lines(&text).unwrap_err();
+        assert!(error.starts_with("line 3:"), "{error}");
+    }
+}
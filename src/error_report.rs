@@ -0,0 +1,137 @@
+//! Details about failures that are shown in notifications, with suggestions
+//! for how they might be fixed.
+
+use std::path::{Path, PathBuf};
+
+use rust_i18n::t;
+
+/// Files that Firefox writes session data to, relative to the profile folder.
+/// Listed in the order they should be tried if another one is damaged.
+const SESSION_FILES: &[&str] = &[
+    "sessionstore-backups/recovery.baklz4",
+    "sessionstore-backups/recovery.jsonlz4",
+    "sessionstore-backups/previous.jsonlz4",
+    "sessionstore.jsonlz4",
+];
+
+/// What was being done when an error occurred.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailedTask {
+    ReadFile,
+    Decompress,
+    Parse,
+    ListWindows,
+    SaveLinks,
+}
+
+/// An error together with the file it concerns.
+#[derive(Debug, Clone)]
+pub struct ErrorReport {
+    pub task: FailedTask,
+    pub error: String,
+    pub path: PathBuf,
+}
+impl ErrorReport {
+    pub fn new(task: FailedTask, path: impl Into<PathBuf>, error: impl Into<String>) -> Self {
+        Self {
+            task,
+            error: error.into(),
+            path: path.into(),
+        }
+    }
+
+    /// Single line description that is shown in the status bar.
+    pub fn summary(&self) -> String {
+        let error = &self.error;
+        match self.task {
+            FailedTask::ReadFile => t!("status.read_failed", error = error),
+            FailedTask::Decompress => t!("status.decompress_failed", error = error),
+            FailedTask::Parse => t!("status.parse_failed", error = error),
+            FailedTask::ListWindows => t!("status.list_windows_failed", error = error),
+            FailedTask::SaveLinks => t!("status.save_failed", error = error),
+        }
+        .into_owned()
+    }
+
+    /// Short title that doesn't include the error message.
+    pub fn title(&self) -> String {
+        match self.task {
+            FailedTask::ReadFile => t!("error_report.read_title"),
+            FailedTask::Decompress | FailedTask::Parse | FailedTask::ListWindows => {
+                t!("error_report.load_title")
+            }
+            FailedTask::SaveLinks => t!("error_report.save_title"),
+        }
+        .into_owned()
+    }
+
+    /// The error followed by the errors that caused it. Errors are formatted
+    /// as `context: cause` so the message is split at each `": "`.
+    pub fn chain(&self) -> Vec<&str> {
+        self.error.split(": ").map(str::trim).collect()
+    }
+
+    /// Another session file in the same Firefox profile that can be loaded if
+    /// this one seems to be damaged.
+    pub fn alternative_file(&self) -> Option<PathBuf> {
+        if !matches!(
+            self.task,
+            FailedTask::Decompress | FailedTask::Parse | FailedTask::ListWindows
+        ) {
+            return None;
+        }
+        let folder = self.path.parent()?;
+        let profile = if folder.file_name()? == "sessionstore-backups" {
+            folder.parent()?
+        } else {
+            folder
+        };
+        SESSION_FILES
+            .iter()
+            .map(|file| profile.join(file))
+            .find(|file| file.is_file() && !same_file(file, &self.path))
+    }
+
+    /// Advice about how the error might be fixed.
+    pub fn suggestion(&self) -> Option<String> {
+        let suggestion = match self.task {
+            FailedTask::ReadFile if !self.path.exists() => t!("error_report.missing_file"),
+            FailedTask::ReadFile => t!("error_report.unreadable_file"),
+            FailedTask::Decompress | FailedTask::Parse | FailedTask::ListWindows => {
+                match self.alternative_file() {
+                    Some(file) => t!(
+                        "error_report.try_other_file",
+                        file = file.file_name()?.to_string_lossy()
+                    ),
+                    None => t!("error_report.damaged_file"),
+                }
+            }
+            FailedTask::SaveLinks if self.path.is_file() => t!("error_report.file_exists"),
+            FailedTask::SaveLinks if self.path.parent().is_some_and(|folder| !folder.exists()) => {
+                t!("error_report.missing_folder")
+            }
+            FailedTask::SaveLinks => return None,
+        };
+        Some(suggestion.into_owned())
+    }
+
+    /// Plain text with all details, for copying into bug reports.
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{}\n{}\n", self.title(), self.path.display());
+        for (depth, error) in self.chain().into_iter().enumerate() {
+            text.push_str(&format!("{}{error}\n", "  ".repeat(depth)));
+        }
+        if let Some(suggestion) = self.suggestion() {
+            text.push_str(&suggestion);
+            text.push('\n');
+        }
+        text
+    }
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
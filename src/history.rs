@@ -0,0 +1,53 @@
+//! Undo and redo of changes to the user's choices.
+
+/// Max number of changes that can be undone.
+const MAX_ENTRIES: usize = 100;
+
+/// Earlier and later versions of some state, stored as full snapshots.
+#[derive(Debug, Clone)]
+pub struct History<T> {
+    undo: Vec<T>,
+    redo: Vec<T>,
+}
+impl<T> Default for History<T> {
+    fn default() -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+        }
+    }
+}
+impl<T> History<T> {
+    /// Remember the state from before a change. Changes that were undone can't
+    /// be redone after this.
+    pub fn record(&mut self, previous: T) {
+        if self.undo.len() >= MAX_ENTRIES {
+            self.undo.remove(0);
+        }
+        self.undo.push(previous);
+        self.redo.clear();
+    }
+    /// Get the state from before the latest change. `current` is kept so that
+    /// the change can be redone.
+    pub fn undo(&mut self, current: T) -> Option<T> {
+        let previous = self.undo.pop()?;
+        self.redo.push(current);
+        Some(previous)
+    }
+    /// Get the state from before the latest undo. `current` is kept so that
+    /// the change can be undone again.
+    pub fn redo(&mut self, current: T) -> Option<T> {
+        let next = self.redo.pop()?;
+        self.undo.push(current);
+        Some(next)
+    }
+    /// Change every remembered state, for example when the things that they
+    /// refer to have moved.
+    pub fn update_all(&mut self, mut f: impl FnMut(&mut T)) {
+        self.undo.iter_mut().chain(&mut self.redo).for_each(&mut f);
+    }
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
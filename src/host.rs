@@ -4,14 +4,23 @@
 )]
 
 use std::{
-    borrow::Cow, env, future::Future, io::Empty, path::PathBuf, sync::Arc, time::SystemTime,
+    borrow::Cow,
+    collections::{BTreeMap, HashSet},
+    env,
+    future::Future,
+    io::Empty,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
 };
 
 use either::Either;
 #[cfg(feature = "real_data")]
-use firefox_session_data::session_store::FirefoxSessionStore;
+use firefox_session_data::session_store::{session_info::TreeDataSource, FirefoxSessionStore};
 #[cfg(feature = "real_data")]
 pub use firefox_session_data::to_links::ttl_formats::FormatInfo;
+use rust_i18n::t;
+use serde::{Deserialize, Serialize};
 
 /// Unconditionally sendable when targeting the web.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -25,6 +34,20 @@ unsafe impl<T> Send for WebSendable<T> {}
 #[cfg(all(target_family = "wasm", target_os = "unknown"))]
 unsafe impl<T> Sync for WebSendable<T> {}
 
+/// Only keep the items at the specified indexes. Keeps all items if no indexes
+/// are specified.
+fn retain_indexes<T>(items: &mut Vec<T>, indexes: Option<&Vec<u32>>) {
+    let Some(indexes) = indexes else {
+        return;
+    };
+    let mut ix = 0;
+    items.retain(|_| {
+        let keep = indexes.contains(&ix);
+        ix += 1;
+        keep
+    });
+}
+
 /// A version of [`tokio::task::spawn_blocking`] that works for the WebAssembly
 /// target where we don't have access to threads, in that case we simply block
 /// the runtime (i.e. the event loop).
@@ -97,21 +120,46 @@ mod fake {
         pub async fn get_groups_from_session(
             &self,
             sort_groups: bool,
+            tree_sources: &[TreeSource],
         ) -> Result<AllTabGroups, String> {
+            let tab = |index: u32, title: &str, parent: Option<u32>| TabInfo {
+                index,
+                title: title.into(),
+                url: format!("http://www.example.com/{index}"),
+                parent,
+                last_accessed: Some(1_700_000_000_000 + u64::from(index) * 60_000),
+                pinned: index == 0,
+            };
             Ok(AllTabGroups {
                 open: vec![
                     TabGroup {
                         index: 0,
                         name: "Window 1".into(),
+                        tabs: vec![
+                            tab(0, "Research", None),
+                            tab(1, "Paper", Some(0)),
+                            tab(2, "Citation", Some(1)),
+                            tab(3, "News", None),
+                        ],
+                        closed_at: None,
                     },
                     TabGroup {
                         index: 1,
                         name: "Window 2".into(),
+                        tabs: vec![tab(0, "Example", None)],
+                        closed_at: None,
                     },
                 ],
                 closed: vec![TabGroup {
                     index: 2,
                     name: "Closed window 1".into(),
+                    tabs: vec![tab(0, "Closed tab", None)],
+                    closed_at: Some(1_700_000_300_000),
+                }],
+                closed_tabs: vec![ClosedTabInfo {
+                    window: "Window 1".into(),
+                    tab: tab(0, "Recently closed tab", None),
+                    closed_at: Some(1_700_000_600_000),
                 }],
             })
         }
@@ -119,6 +167,7 @@ mod fake {
         pub async fn to_text_links(
             &self,
             generate_options: GenerateOptions,
+            link_options: LinkOptions,
         ) -> Result<String, String> {
             Ok("http://www.example.com".to_string())
         }
@@ -128,10 +177,34 @@ mod fake {
             save_path: PathBuf,
             generate_options: GenerateOptions,
             output_options: OutputOptions,
-        ) -> Result<(), String> {
-            Ok(())
+            link_options: LinkOptions,
+        ) -> Result<PathBuf, String> {
+            Ok(save_path)
+        }
+
+        pub async fn render_pdf_preview(
+            &self,
+            generate_options: GenerateOptions,
+            link_options: LinkOptions,
+            format: FormatInfo,
+        ) -> Result<Vec<PdfPreviewPage>, String> {
+            Ok(Vec::new())
         }
     }
+
+    pub fn is_pdf_format(format: FormatInfo) -> bool {
+        matches!(format, FormatInfo::PDF)
+    }
+
+    pub async fn save_text_file(
+        save_path: PathBuf,
+        text: String,
+        file_ext: String,
+        overwrite: bool,
+        create_folder: bool,
+    ) -> Result<PathBuf, String> {
+        Ok(save_path)
+    }
 }
 #[cfg(not(feature = "real_data"))]
 pub use fake::*;
@@ -232,14 +305,26 @@ where
     }
 }
 
+/// File extensions of Firefox session files that can be loaded.
+pub const SESSION_FILE_EXTENSIONS: &[&str] = &["js", "baklz4", "jsonlz4"];
+
+/// Check if a file looks like a Firefox session file that can be loaded.
+pub fn is_session_file(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| {
+        SESSION_FILE_EXTENSIONS
+            .iter()
+            .any(|supported| ext.eq_ignore_ascii_case(supported))
+    })
+}
+
 /// Also consider [`gpui::App::prompt_for_paths`].
 pub fn prompt_load_file(
     parent: Option<&dyn DialogParent>,
 ) -> impl Future<Output = Option<rfd::FileHandle>> + 'static {
     let mut builder = ::rfd::AsyncFileDialog::new()
-        .add_filter("Firefox session file", &["js", "baklz4", "jsonlz4"])
-        .add_filter("All files", &["*"])
-        .set_title("Open Firefox Sessionstore File");
+        .add_filter(t!("dialog.session_files"), SESSION_FILE_EXTENSIONS)
+        .add_filter(t!("dialog.all_files"), &["*"])
+        .set_title(t!("dialog.open_title"));
 
     if let Some(parent) = parent {
         builder = builder.set_parent(&parent);
@@ -253,6 +338,22 @@ pub fn prompt_load_file(
     builder.pick_file()
 }
 
+/// Prompt for a recording of messages that was saved by the inspector.
+pub fn prompt_load_recording(
+    parent: Option<&dyn DialogParent>,
+) -> impl Future<Output = Option<rfd::FileHandle>> + 'static {
+    let mut builder = rfd::AsyncFileDialog::new()
+        .add_filter(t!("dialog.recordings"), &["jsonl"])
+        .add_filter(t!("dialog.all_files"), &["*"])
+        .set_title(t!("dialog.open_recording_title"));
+
+    if let Some(parent) = parent {
+        builder = builder.set_parent(&parent);
+    }
+
+    builder.pick_file()
+}
+
 /// Also consider [`gpui::App::prompt_for_new_path`].
 pub fn prompt_save_file(
     parent: Option<&dyn DialogParent>,
@@ -266,7 +367,7 @@ pub fn prompt_save_file(
         //.add_filter("Markdown files", &["md"])
         //.add_filter("Typst files", &["typ"])
         //.add_filter("PDF files", &["pdf"])
-        .set_title("Save Links from Firefox Tabs");
+        .set_title(t!("dialog.save_title"));
 
     if let Some(parent) = parent {
         builder = builder.set_parent(&parent);
@@ -275,6 +376,49 @@ pub fn prompt_save_file(
     builder.save_file()
 }
 
+/// Open URLs using a browser command such as `firefox --new-window`. The URLs
+/// are appended as extra arguments.
+#[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+pub fn open_urls_with_command(command: &str, urls: &[String]) -> Result<(), String> {
+    let mut parts = command.split_whitespace();
+    let program = parts
+        .next()
+        .ok_or_else(|| t!("error.browser_command_empty").into_owned())?;
+    std::process::Command::new(program)
+        .args(parts)
+        .args(urls)
+        .spawn()
+        .map_err(|e| t!("error.start_program", program = program, error = e).into_owned())?;
+    Ok(())
+}
+
+/// Format a timestamp in milliseconds since the Unix epoch using the local
+/// time zone.
+pub fn format_timestamp(millis: u64) -> Option<String> {
+    let time = chrono::DateTime::from_timestamp_millis(millis as i64)?;
+    Some(
+        time.with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string(),
+    )
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TabInfo {
+    /// The index of the tab inside its tab group.
+    pub index: u32,
+    /// Title of the tab's current page.
+    pub title: String,
+    /// URL of the tab's current page.
+    pub url: String,
+    /// Index of the parent tab in the tree of tabs provided by Sidebery or Tree
+    /// Style Tab. `None` for tabs at the root of the tree.
+    pub parent: Option<u32>,
+    /// When the tab was last accessed, in milliseconds since the Unix epoch.
+    pub last_accessed: Option<u64>,
+    pub pinned: bool,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TabGroup {
     /// The index of the tab group. Used to uniquely identify a group in a
@@ -282,6 +426,44 @@ pub struct TabGroup {
     pub index: u32,
     /// Name of a tab group.
     pub name: String,
+    /// Tabs in the group.
+    pub tabs: Vec<TabInfo>,
+    /// When a closed window was closed, in milliseconds since the Unix epoch.
+    pub closed_at: Option<u64>,
+}
+impl TabGroup {
+    /// The most recent time that any tab in the group was accessed.
+    pub fn last_accessed(&self) -> Option<u64> {
+        self.tabs.iter().filter_map(|tab| tab.last_accessed).max()
+    }
+
+    /// Indexes of a tab and all of its descendants in the tab tree.
+    pub fn subtree(&self, tab_index: u32) -> Vec<u32> {
+        let mut subtree = vec![tab_index];
+        let mut ix = 0;
+        while let Some(&parent) = subtree.get(ix) {
+            for tab in &self.tabs {
+                // Ignore cycles in the tree data:
+                if tab.parent == Some(parent) && !subtree.contains(&tab.index) {
+                    subtree.push(tab.index);
+                }
+            }
+            ix += 1;
+        }
+        subtree
+    }
+}
+
+/// A tab that was closed inside an open or closed window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClosedTabInfo {
+    /// Name of the window that the tab was closed in.
+    pub window: String,
+    /// Info about the tab. The index is its position in the window's list of
+    /// closed tabs.
+    pub tab: TabInfo,
+    /// When the tab was closed, in milliseconds since the Unix epoch.
+    pub closed_at: Option<u64>,
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
@@ -290,12 +472,54 @@ pub struct AllTabGroups {
     pub open: Vec<TabGroup>,
     /// Tab groups or windows in recently closed windows.
     pub closed: Vec<TabGroup>,
+    /// Recently closed tabs from both open and closed windows.
+    pub closed_tabs: Vec<ClosedTabInfo>,
+}
+impl AllTabGroups {
+    /// Tab groups and tabs that are included by the generate options.
+    pub fn selected_groups<'a>(
+        &'a self,
+        options: &'a GenerateOptions,
+    ) -> impl Iterator<Item = TabGroup> + 'a {
+        let open = self.open.iter().map(|group| {
+            (
+                group,
+                &options.open_group_indexes,
+                options.open_tab_indexes.get(&group.index),
+            )
+        });
+        let closed = self.closed.iter().map(|group| {
+            (
+                group,
+                &options.closed_group_indexes,
+                options.closed_tab_indexes.get(&group.index),
+            )
+        });
+        open.chain(closed)
+            .filter(|(group, indexes, _)| {
+                indexes
+                    .as_ref()
+                    .is_none_or(|indexes| indexes.contains(&group.index))
+            })
+            .map(|(group, _, tab_indexes)| {
+                let mut group = group.clone();
+                if let Some(tab_indexes) = tab_indexes {
+                    group.tabs.retain(|tab| tab_indexes.contains(&tab.index));
+                }
+                group
+            })
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GenerateOptions {
     pub open_group_indexes: Option<Vec<u32>>,
     pub closed_group_indexes: Option<Vec<u32>>,
+    /// Only include some tabs from these open groups. Groups that aren't
+    /// present here include all of their tabs.
+    pub open_tab_indexes: BTreeMap<u32, Vec<u32>>,
+    /// Only include some tabs from these closed groups.
+    pub closed_tab_indexes: BTreeMap<u32, Vec<u32>>,
     pub sort_groups: bool,
     pub table_of_content: bool,
 }
@@ -305,17 +529,184 @@ impl GenerateOptions {
             + self.closed_group_indexes.as_ref().map_or(0, Vec::len)
     }
 }
+#[cfg(feature = "real_data")]
+impl GenerateOptions {
+    /// Only keep the groups and tabs from a session that are included by these
+    /// options. `tabs` gets the list of tabs in a group.
+    fn select_groups<G, T>(
+        &self,
+        open: impl Iterator<Item = G>,
+        closed: impl Iterator<Item = G>,
+        mut tabs: impl FnMut(&mut G) -> &mut Vec<T>,
+    ) -> Vec<G> {
+        let open = open.enumerate().map(|(ix, group)| {
+            (
+                ix as u32,
+                group,
+                &self.open_group_indexes,
+                &self.open_tab_indexes,
+            )
+        });
+        let closed = closed.enumerate().map(|(ix, group)| {
+            (
+                ix as u32,
+                group,
+                &self.closed_group_indexes,
+                &self.closed_tab_indexes,
+            )
+        });
+        open.chain(closed)
+            .filter(|(ix, _, indexes, _)| {
+                indexes.as_ref().is_none_or(|indexes| indexes.contains(ix))
+            })
+            .map(|(ix, mut group, _, tab_indexes)| {
+                retain_indexes(tabs(&mut group), tab_indexes.get(&ix));
+                group
+            })
+            .collect()
+    }
+}
 impl Default for GenerateOptions {
     fn default() -> Self {
         Self {
             open_group_indexes: None,
             closed_group_indexes: Some(Vec::new()),
+            open_tab_indexes: BTreeMap::new(),
+            closed_tab_indexes: BTreeMap::new(),
             sort_groups: true,
             table_of_content: true,
         }
     }
 }
 
+/// New position of a window and its tabs after a session file changed.
+#[derive(Debug, Clone)]
+struct MovedGroup {
+    index: u32,
+    /// New index of each tab that is still in the window.
+    tabs: BTreeMap<u32, u32>,
+}
+
+/// Where the windows and tabs of a session ended up after its file was
+/// written again, for example by a running Firefox. Indexes can change
+/// between writes, so windows are matched by the URLs of their tabs and tabs
+/// by their URL.
+#[derive(Debug, Clone, Default)]
+pub struct SessionChanges {
+    /// Moved open windows followed by moved closed windows, by old index.
+    groups: [BTreeMap<u32, MovedGroup>; 2],
+}
+impl SessionChanges {
+    pub fn new(old: &AllTabGroups, new: &AllTabGroups) -> Self {
+        Self {
+            groups: [
+                Self::match_groups(&old.open, &new.open),
+                Self::match_groups(&old.closed, &new.closed),
+            ],
+        }
+    }
+
+    /// Match each old window with the new window that shares the most tabs
+    /// with it, preferring windows with the same name.
+    fn match_groups(old: &[TabGroup], new: &[TabGroup]) -> BTreeMap<u32, MovedGroup> {
+        let mut unmatched = new.iter().collect::<Vec<_>>();
+        let mut moved = BTreeMap::new();
+        for group in old {
+            let urls = group
+                .tabs
+                .iter()
+                .map(|tab| tab.url.as_str())
+                .collect::<HashSet<_>>();
+            let score = |candidate: &TabGroup| {
+                let shared = candidate
+                    .tabs
+                    .iter()
+                    .filter(|tab| urls.contains(tab.url.as_str()))
+                    .count();
+                (shared, candidate.name == group.name)
+            };
+            let best = unmatched
+                .iter()
+                .enumerate()
+                .map(|(ix, candidate)| (score(candidate), ix))
+                .max_by_key(|&((shared, same_name), ix)| (shared, same_name, usize::MAX - ix));
+            let Some(((shared, same_name), ix)) = best else {
+                break;
+            };
+            // Windows without tabs can only be recognized by their name:
+            if shared == 0 && !(group.tabs.is_empty() && same_name) {
+                continue;
+            }
+            let candidate = unmatched.remove(ix);
+            let mut new_tabs = candidate.tabs.iter().collect::<Vec<_>>();
+            let tabs = group
+                .tabs
+                .iter()
+                .filter_map(|tab| {
+                    let ix = new_tabs.iter().position(|new_tab| new_tab.url == tab.url)?;
+                    Some((tab.index, new_tabs.remove(ix).index))
+                })
+                .collect();
+            moved.insert(
+                group.index,
+                MovedGroup {
+                    index: candidate.index,
+                    tabs,
+                },
+            );
+        }
+        moved
+    }
+
+    fn moved_group(&self, open: bool, group: u32) -> Option<&MovedGroup> {
+        self.groups[usize::from(!open)].get(&group)
+    }
+
+    /// New index of a window, if it still exists.
+    pub fn group(&self, open: bool, group: u32) -> Option<u32> {
+        Some(self.moved_group(open, group)?.index)
+    }
+
+    /// New index of a tab inside its window's new position, if it still
+    /// exists.
+    pub fn tab(&self, open: bool, group: u32, tab: u32) -> Option<u32> {
+        self.moved_group(open, group)?.tabs.get(&tab).copied()
+    }
+
+    /// Options that select the same windows and tabs after the change.
+    /// Windows and tabs that no longer exist are left out.
+    pub fn apply(&self, options: &GenerateOptions) -> GenerateOptions {
+        let groups = |open: bool, indexes: &Option<Vec<u32>>| {
+            indexes.as_ref().map(|indexes| {
+                indexes
+                    .iter()
+                    .filter_map(|&group| self.group(open, group))
+                    .collect()
+            })
+        };
+        let tabs = |open: bool, indexes: &BTreeMap<u32, Vec<u32>>| {
+            indexes
+                .iter()
+                .filter_map(|(&group, tabs)| {
+                    let moved = self.moved_group(open, group)?;
+                    let tabs = tabs
+                        .iter()
+                        .filter_map(|tab| moved.tabs.get(tab).copied())
+                        .collect();
+                    Some((moved.index, tabs))
+                })
+                .collect()
+        };
+        GenerateOptions {
+            open_group_indexes: groups(true, &options.open_group_indexes),
+            closed_group_indexes: groups(false, &options.closed_group_indexes),
+            open_tab_indexes: tabs(true, &options.open_tab_indexes),
+            closed_tab_indexes: tabs(false, &options.closed_tab_indexes),
+            ..options.clone()
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct OutputOptions {
     pub format: FormatInfo,
@@ -333,6 +724,109 @@ impl Default for OutputOptions {
     }
 }
 
+/// A source of tree data for tabs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TreeSource {
+    Sidebery,
+    TstWebExtension,
+    TstLegacy,
+}
+impl TreeSource {
+    pub fn all() -> &'static [Self] {
+        &[Self::Sidebery, Self::TstWebExtension, Self::TstLegacy]
+    }
+    pub fn title(&self) -> String {
+        match self {
+            TreeSource::Sidebery => t!("tree_source.sidebery"),
+            TreeSource::TstWebExtension => t!("tree_source.tst_web_extension"),
+            TreeSource::TstLegacy => t!("tree_source.tst_legacy"),
+        }
+        .into_owned()
+    }
+    #[cfg(feature = "real_data")]
+    fn to_tree_data_source(self) -> TreeDataSource {
+        match self {
+            TreeSource::Sidebery => TreeDataSource::Sidebery,
+            TreeSource::TstWebExtension => TreeDataSource::TstWebExtension,
+            TreeSource::TstLegacy => TreeDataSource::TstLegacy,
+        }
+    }
+}
+
+/// Options that affect how tabs are converted to links.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkOptions {
+    /// Insert a page break after each tab group. `None` uses the default for
+    /// the output format which is to have page breaks in everything except
+    /// raw text.
+    pub page_breaks_after_group: Option<bool>,
+    /// Don't insert a page break after the last group. Only used for HTML and
+    /// Typst based formats.
+    pub skip_page_break_after_last_group: bool,
+    /// Indent links even if they have no parent in the tree data.
+    pub indent_all_links: bool,
+    /// Text to use as a page break instead of the output format's default.
+    pub custom_page_break: String,
+    /// Sources of tree data for tabs. The first source that has any data is
+    /// used.
+    pub tree_sources: Vec<TreeSource>,
+}
+impl Default for LinkOptions {
+    fn default() -> Self {
+        Self {
+            page_breaks_after_group: None,
+            skip_page_break_after_last_group: true,
+            indent_all_links: true,
+            custom_page_break: String::new(),
+            tree_sources: TreeSource::all().to_vec(),
+        }
+    }
+}
+#[cfg(feature = "real_data")]
+impl LinkOptions {
+    fn to_links_options(
+        &self,
+        format: firefox_session_data::session_store::to_links::LinkFormat,
+        default_page_breaks: bool,
+        table_of_contents: bool,
+    ) -> firefox_session_data::session_store::to_links::ToLinksOptions {
+        let page_breaks = self.page_breaks_after_group.unwrap_or(default_page_breaks);
+        firefox_session_data::session_store::to_links::ToLinksOptions {
+            format,
+            page_breaks_after_group: page_breaks,
+            skip_page_break_after_last_group: page_breaks
+                && self.skip_page_break_after_last_group
+                && (format.is_html() || format.is_typst()),
+            table_of_contents,
+            indent_all_links: self.indent_all_links,
+            custom_page_break: self.custom_page_break.clone().into(),
+            tree_sources: self
+                .tree_sources
+                .iter()
+                .map(|source| source.to_tree_data_source())
+                .collect::<Vec<_>>()
+                .into(),
+        }
+    }
+}
+
+/// A rasterized page of a generated PDF file.
+#[derive(Debug, Clone)]
+pub struct PdfPreviewPage {
+    /// Width of the page in PDF points.
+    pub width: f32,
+    /// Height of the page in PDF points.
+    pub height: f32,
+    /// The page rendered as a PNG image.
+    pub png: Arc<[u8]>,
+}
+
+/// Check if an output format will be converted to a PDF file.
+#[cfg(feature = "real_data")]
+pub fn is_pdf_format(format: FormatInfo) -> bool {
+    format.as_format().to_link_format().1.is_some()
+}
+
 #[derive(Debug, Clone)]
 pub enum FileData {
     Compressed(Arc<[u8]>),
@@ -381,7 +875,7 @@ impl FileInfo {
         let data = self
             .file_handle
             .as_ref()
-            .ok_or("no file handle for the specified path")?
+            .ok_or_else(|| t!("error.no_file_handle").into_owned())?
             .0
             .read()
             .await;
@@ -395,14 +889,15 @@ impl FileInfo {
 
             let path = self.file_path.clone();
             spawn_blocking(move || -> Result<_, String> {
-                let file = File::open(&*path)
-                    .map_err(|e| format!("failed to open file at {}: {e}", path.display()))?;
+                let file = File::open(&*path).map_err(|e| {
+                    t!("error.open_file", path = path.display(), error = e).into_owned()
+                })?;
 
                 let mut buffer = BufReader::new(file);
                 let mut data = Vec::new();
 
                 buffer.read_to_end(&mut data).map_err(|e| {
-                    format!("failed to read file data from {}: {e}", path.display())
+                    t!("error.read_file", path = path.display(), error = e).into_owned()
                 })?;
 
                 Ok(data)
@@ -423,7 +918,7 @@ impl FileInfo {
         let data = match self
             .data
             .as_ref()
-            .ok_or("Tried to decompress data before reading it")?
+            .ok_or_else(|| t!("error.decompress_before_read").into_owned())?
         {
             FileData::Compressed(data) => data.clone(),
             FileData::Uncompressed(_) | FileData::Parsed(_) => return Ok(()),
@@ -433,7 +928,7 @@ impl FileInfo {
                 Vec::<u8>::from(&*data).into(),
             ))
             .map(|reader| -> Vec<u8> { reader.into() })
-            .map_err(|e| format!("failed to decompress data: {e}"))
+            .map_err(|e| t!("error.decompress", error = e).into_owned())
         })
         .await?;
 
@@ -444,30 +939,41 @@ impl FileInfo {
         let data = match self
             .data
             .as_ref()
-            .ok_or("Tried to parse data before reading it")?
+            .ok_or_else(|| t!("error.parse_before_read").into_owned())?
         {
-            FileData::Compressed(_) => return Err("can't parse compressed data".to_string()),
+            FileData::Compressed(_) => return Err(t!("error.parse_compressed").into_owned()),
             FileData::Uncompressed(data) => data.clone(),
             FileData::Parsed(_) => return Ok(()),
         };
         let session = spawn_blocking(move || {
             serde_json::from_slice::<FirefoxSessionStore>(&data)
-                .map_err(|e| format!("failed to parse sessionstore JSON data: {e}"))
+                .map_err(|e| t!("error.parse_json", error = e).into_owned())
         })
         .await?;
 
         self.data = Some(FileData::Parsed(Arc::new(session)));
         Ok(())
     }
-    pub async fn get_groups_from_session(&self, sort_groups: bool) -> Result<AllTabGroups, String> {
+    /// List the tab groups in the session. Tab parents are found using the
+    /// first of `tree_sources` that has any data, same as for generated links.
+    pub async fn get_groups_from_session(
+        &self,
+        sort_groups: bool,
+        tree_sources: &[TreeSource],
+    ) -> Result<AllTabGroups, String> {
         use firefox_session_data::session_store::session_info::get_groups_from_session;
 
+        let tree_sources = tree_sources
+            .iter()
+            .map(|source| source.to_tree_data_source())
+            .collect::<Vec<_>>();
+
         let session = self
             .data
             .as_ref()
             .and_then(FileData::as_parsed)
             .cloned()
-            .ok_or("must deserialize JSON sessionstore data before tab groups can be inspected")?;
+            .ok_or_else(|| t!("error.list_before_parse").into_owned())?;
 
         Ok(spawn_blocking(move || AllTabGroups {
             open: get_groups_from_session(&session, true, false, sort_groups)
@@ -475,6 +981,20 @@ impl FileInfo {
                 .map(|(ix, group)| TabGroup {
                     index: ix as _,
                     name: group.name().to_owned(),
+                    tabs: group
+                        .tabs
+                        .iter()
+                        .enumerate()
+                        .map(|(ix, tab)| TabInfo {
+                            index: ix as _,
+                            title: tab.title().to_owned(),
+                            url: tab.url().to_owned(),
+                            parent: tab.tree_parent_index(&tree_sources).map(|ix| ix as _),
+                            last_accessed: tab.last_accessed(),
+                            pinned: tab.pinned(),
+                        })
+                        .collect(),
+                    closed_at: None,
                 })
                 .collect::<Vec<_>>(),
             closed: get_groups_from_session(&session, false, true, sort_groups)
@@ -482,21 +1002,59 @@ impl FileInfo {
                 .map(|(ix, group)| TabGroup {
                     index: ix as _,
                     name: group.name().to_owned(),
+                    tabs: group
+                        .tabs
+                        .iter()
+                        .enumerate()
+                        .map(|(ix, tab)| TabInfo {
+                            index: ix as _,
+                            title: tab.title().to_owned(),
+                            url: tab.url().to_owned(),
+                            parent: tab.tree_parent_index(&tree_sources).map(|ix| ix as _),
+                            last_accessed: tab.last_accessed(),
+                            pinned: tab.pinned(),
+                        })
+                        .collect(),
+                    closed_at: group.closed_at(),
                 })
                 .collect::<Vec<_>>(),
+            closed_tabs: session
+                .windows
+                .iter()
+                .chain(&session.closed_windows)
+                .enumerate()
+                .flat_map(|(window_ix, window)| {
+                    window
+                        .closed_tabs
+                        .iter()
+                        .enumerate()
+                        .map(move |(ix, closed)| ClosedTabInfo {
+                            window: t!("session.window_name", number = window_ix + 1).into_owned(),
+                            tab: TabInfo {
+                                index: ix as _,
+                                title: closed.state.title().to_owned(),
+                                url: closed.state.url().to_owned(),
+                                parent: None,
+                                last_accessed: closed.state.last_accessed(),
+                                pinned: closed.state.pinned(),
+                            },
+                            closed_at: closed.closed_at,
+                        })
+                })
+                .collect(),
         })
         .await)
     }
 
     /// Generate a text only representation of the sessionstore data.
-    pub async fn to_text_links(&self, generate_options: GenerateOptions) -> Result<String, String> {
+    pub async fn to_text_links(
+        &self,
+        generate_options: GenerateOptions,
+        link_options: LinkOptions,
+    ) -> Result<String, String> {
         use firefox_session_data::{
             pdf_converter::html_to_pdf::WriteBuilderSimple,
-            session_store::{
-                session_info::{get_groups_from_session, TreeDataSource},
-                to_links::LinkFormat,
-                to_links::ToLinksOptions,
-            },
+            session_store::{session_info::get_groups_from_session, to_links::LinkFormat},
             to_links::TabsToLinksOutput,
         };
 
@@ -505,56 +1063,29 @@ impl FileInfo {
             .as_ref()
             .and_then(FileData::as_parsed)
             .cloned()
-            .ok_or("must deserialize JSON sessionstore data before converting tabs to links")?;
+            .ok_or_else(|| t!("error.convert_before_parse").into_owned())?;
 
         spawn_blocking(move || {
             let mut output: Vec<u8> = Vec::new();
 
-            let open_groups =
-                get_groups_from_session(&session, true, false, generate_options.sort_groups)
-                    .enumerate()
-                    .filter(|(ix, _)| {
-                        if let Some(indexes) = &generate_options.open_group_indexes {
-                            indexes.contains(&(*ix as u32))
-                        } else {
-                            true
-                        }
-                    })
-                    .map(|(_, g)| g);
-
-            let closed_groups =
-                get_groups_from_session(&session, false, true, generate_options.sort_groups)
-                    .enumerate()
-                    .filter(|(ix, _)| {
-                        if let Some(indexes) = &generate_options.closed_group_indexes {
-                            indexes.contains(&(*ix as u32))
-                        } else {
-                            true
-                        }
-                    })
-                    .map(|(_, g)| g);
+            let groups = generate_options.select_groups(
+                get_groups_from_session(&session, true, false, generate_options.sort_groups),
+                get_groups_from_session(&session, false, true, generate_options.sort_groups),
+                |group| &mut group.tabs,
+            );
 
             firefox_session_data::tabs_to_links(
-                &open_groups.chain(closed_groups).collect::<Vec<_>>(),
+                &groups,
                 TabsToLinksOutput {
                     format: LinkFormat::TXT,
                     as_pdf: None,
-                    conversion_options: ToLinksOptions {
-                        format: LinkFormat::TXT,
-                        page_breaks_after_group: false, // We don't have any page break character in raw text
-                        skip_page_break_after_last_group: true,
-                        table_of_contents: generate_options.table_of_content,
-                        indent_all_links: true,
-                        custom_page_break: "".into(),
-                        // If there is any data from Sidebery then TST data
-                        // won't be used and so on:
-                        tree_sources: (&[
-                            TreeDataSource::Sidebery,
-                            TreeDataSource::TstWebExtension,
-                            TreeDataSource::TstLegacy,
-                        ] as &[_])
-                            .into(),
-                    },
+                    // We don't have any page break character in raw text so
+                    // page breaks are only used if explicitly requested:
+                    conversion_options: link_options.to_links_options(
+                        LinkFormat::TXT,
+                        false,
+                        generate_options.table_of_content,
+                    ),
                 },
                 WriteBuilderSimple(&mut output),
             )
@@ -564,20 +1095,19 @@ impl FileInfo {
         })
         .await
     }
-    #[cfg_attr(target_family = "wasm", expect(unused_mut, unused_variables))]
+    /// Returns the path that the file was written to, which includes the file
+    /// extension if one was added.
+    #[cfg_attr(target_family = "wasm", expect(unused_mut))]
     pub async fn save_links(
         &self,
         mut save_path: PathBuf,
         generate_options: GenerateOptions,
         output_options: OutputOptions,
-    ) -> Result<(), String> {
+        link_options: LinkOptions,
+    ) -> Result<PathBuf, String> {
         use firefox_session_data::{
             pdf_converter::html_to_pdf::WriteBuilderSimple,
-            session_store::{
-                session_info::{get_groups_from_session, TreeDataSource},
-                to_links::LinkFormat,
-                to_links::ToLinksOptions,
-            },
+            session_store::{session_info::get_groups_from_session, to_links::LinkFormat},
             to_links::TabsToLinksOutput,
         };
 
@@ -586,7 +1116,7 @@ impl FileInfo {
             .as_ref()
             .and_then(FileData::as_parsed)
             .cloned()
-            .ok_or("must deserialize JSON sessionstore data before converting tabs to links")?;
+            .ok_or_else(|| t!("error.convert_before_parse").into_owned())?;
 
         spawn_blocking(move || {
             let (format, as_pdf) = output_options.format.as_format().to_link_format();
@@ -610,80 +1140,32 @@ impl FileInfo {
                 }
                 #[cfg(not(target_family = "wasm"))]
                 {
-                    if save_path.extension().is_none() {
-                        save_path.set_extension(file_ext);
-                    }
-
-                    if let Some(folder) = save_path.parent() {
-                        if output_options.create_folder {
-                            std::fs::create_dir_all(folder).map_err(|e| {
-                                format!("failed to create folder at \"{}\": {e}", folder.display())
-                            })?;
-                        }
-                    }
-
-                    std::fs::OpenOptions::new()
-                        .write(true)
-                        .truncate(true)
-                        .create(true)
-                        .create_new(!output_options.overwrite)
-                        .open(&save_path)
-                        .map_err(|e| {
-                            format!(
-                                "failed to create new file at \"{}\": {e}",
-                                save_path.display()
-                            )
-                        })?
+                    create_output_file(
+                        &mut save_path,
+                        file_ext,
+                        output_options.overwrite,
+                        output_options.create_folder,
+                    )?
                 }
             };
 
-            let open_groups =
-                get_groups_from_session(&session, true, false, generate_options.sort_groups)
-                    .enumerate()
-                    .filter(|(ix, _)| {
-                        if let Some(indexes) = &generate_options.open_group_indexes {
-                            indexes.contains(&(*ix as u32))
-                        } else {
-                            true
-                        }
-                    })
-                    .map(|(_, g)| g);
-
-            let closed_groups =
-                get_groups_from_session(&session, false, true, generate_options.sort_groups)
-                    .enumerate()
-                    .filter(|(ix, _)| {
-                        if let Some(indexes) = &generate_options.closed_group_indexes {
-                            indexes.contains(&(*ix as u32))
-                        } else {
-                            true
-                        }
-                    })
-                    .map(|(_, g)| g);
+            let groups = generate_options.select_groups(
+                get_groups_from_session(&session, true, false, generate_options.sort_groups),
+                get_groups_from_session(&session, false, true, generate_options.sort_groups),
+                |group| &mut group.tabs,
+            );
 
             let page_breaks = !matches!(output_options.format, FormatInfo::TEXT);
             firefox_session_data::tabs_to_links(
-                &open_groups.chain(closed_groups).collect::<Vec<_>>(),
+                &groups,
                 TabsToLinksOutput {
                     format,
                     as_pdf,
-                    conversion_options: ToLinksOptions {
+                    conversion_options: link_options.to_links_options(
                         format,
-                        page_breaks_after_group: page_breaks,
-                        skip_page_break_after_last_group: page_breaks
-                            && (format.is_html() || format.is_typst()),
-                        table_of_contents: generate_options.table_of_content,
-                        indent_all_links: true,
-                        custom_page_break: "".into(),
-                        // First found data is used so if there is any data from
-                        // Sidebery then TST data won't be used at all:
-                        tree_sources: (&[
-                            TreeDataSource::Sidebery,
-                            TreeDataSource::TstWebExtension,
-                            TreeDataSource::TstLegacy,
-                        ] as &[_])
-                            .into(),
-                    },
+                        page_breaks,
+                        generate_options.table_of_content,
+                    ),
                 },
                 WriteBuilderSimple(&mut file),
             )
@@ -692,10 +1174,148 @@ impl FileInfo {
             #[cfg(target_family = "wasm")]
             save_file_on_web_target(file.as_slice(), Some(&format!("firefox-links.{file_ext}")))?;
 
-            Ok(())
+            Ok(save_path)
         })
         .await
     }
+    /// Generate a PDF file in memory and rasterize its pages so that the layout
+    /// produced by the HTML to PDF converter can be inspected before the file
+    /// is saved.
+    pub async fn render_pdf_preview(
+        &self,
+        generate_options: GenerateOptions,
+        link_options: LinkOptions,
+        format: FormatInfo,
+    ) -> Result<Vec<PdfPreviewPage>, String> {
+        use firefox_session_data::{
+            pdf_converter::html_to_pdf::WriteBuilderSimple,
+            session_store::session_info::get_groups_from_session, to_links::TabsToLinksOutput,
+        };
+
+        /// Render pages at a higher resolution than they are displayed at so
+        /// that text stays readable.
+        const RENDER_SCALE: f32 = 2.0;
+
+        let session = self
+            .data
+            .as_ref()
+            .and_then(FileData::as_parsed)
+            .cloned()
+            .ok_or_else(|| t!("error.convert_before_parse").into_owned())?;
+
+        spawn_blocking(move || {
+            let (link_format, as_pdf) = format.as_format().to_link_format();
+            if as_pdf.is_none() {
+                return Err(t!("error.not_pdf", format = format.as_str()).into_owned());
+            }
+
+            let groups = generate_options.select_groups(
+                get_groups_from_session(&session, true, false, generate_options.sort_groups),
+                get_groups_from_session(&session, false, true, generate_options.sort_groups),
+                |group| &mut group.tabs,
+            );
+
+            let mut output: Vec<u8> = Vec::new();
+            firefox_session_data::tabs_to_links(
+                &groups,
+                TabsToLinksOutput {
+                    format: link_format,
+                    as_pdf,
+                    conversion_options: link_options.to_links_options(
+                        link_format,
+                        true,
+                        generate_options.table_of_content,
+                    ),
+                },
+                WriteBuilderSimple(&mut output),
+            )
+            .map_err(|e| e.to_string())?;
+
+            let pdf = hayro::Pdf::new(Arc::new(output))
+                .map_err(|e| t!("error.read_pdf", error = format!("{e:?}")).into_owned())?;
+            let interpreter_settings = hayro::InterpreterSettings::default();
+            let render_settings = hayro::RenderSettings {
+                x_scale: RENDER_SCALE,
+                y_scale: RENDER_SCALE,
+                ..Default::default()
+            };
+
+            Ok(pdf
+                .pages()
+                .iter()
+                .map(|page| {
+                    let pixmap = hayro::render(page, &interpreter_settings, &render_settings);
+                    PdfPreviewPage {
+                        width: pixmap.width() as f32 / RENDER_SCALE,
+                        height: pixmap.height() as f32 / RENDER_SCALE,
+                        png: Arc::from(pixmap.take_png()),
+                    }
+                })
+                .collect())
+        })
+        .await
+    }
+}
+
+/// Create a file that output will be written to. Adds the file extension if the
+/// path doesn't have one.
+#[cfg(all(feature = "real_data", not(target_family = "wasm")))]
+fn create_output_file(
+    save_path: &mut PathBuf,
+    file_ext: &str,
+    overwrite: bool,
+    create_folder: bool,
+) -> Result<std::fs::File, String> {
+    if save_path.extension().is_none() {
+        save_path.set_extension(file_ext);
+    }
+
+    if let Some(folder) = save_path.parent() {
+        if create_folder {
+            std::fs::create_dir_all(folder).map_err(|e| {
+                t!("error.create_folder", path = folder.display(), error = e).into_owned()
+            })?;
+        }
+    }
+
+    std::fs::OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .create(true)
+        .create_new(!overwrite)
+        .open(&*save_path)
+        .map_err(|e| t!("error.create_file", path = save_path.display(), error = e).into_owned())
+}
+
+/// Save text that was generated without the help of `firefox_session_data`,
+/// for example using a [`crate::template::OutputTemplate`]. Returns the path
+/// that the file was written to.
+#[cfg(feature = "real_data")]
+#[cfg_attr(target_family = "wasm", expect(unused_mut, unused_variables))]
+pub async fn save_text_file(
+    mut save_path: PathBuf,
+    text: String,
+    file_ext: String,
+    overwrite: bool,
+    create_folder: bool,
+) -> Result<PathBuf, String> {
+    spawn_blocking(move || {
+        #[cfg(target_family = "wasm")]
+        save_file_on_web_target(text.as_bytes(), Some(&format!("firefox-links.{file_ext}")))?;
+
+        #[cfg(not(target_family = "wasm"))]
+        {
+            use std::io::Write;
+
+            let mut file = create_output_file(&mut save_path, &file_ext, overwrite, create_folder)?;
+            file.write_all(text.as_bytes()).map_err(|e| {
+                t!("error.write_file", path = save_path.display(), error = e).into_owned()
+            })?;
+        }
+
+        Ok(save_path)
+    })
+    .await
 }
 
 /// Save some data to a file and download it via the user's browser.
@@ -711,20 +1331,20 @@ fn save_file_on_web_target(data: &[u8], file_name: Option<&str>) -> Result<(), S
     let byte_array = js_sys::Uint8Array::new_with_length(
         data.len()
             .try_into()
-            .map_err(|e| format!("Output file size was larger than a 32 bit number {e}"))?,
+            .map_err(|e| t!("error.file_too_large", error = e).into_owned())?,
     );
     byte_array.copy_from(data);
     let array = js_sys::Array::of1(&byte_array);
     let blob = web_sys::Blob::new_with_u8_array_sequence(&array)
         .ok()
-        .ok_or("Blob creation failed")?;
+        .ok_or_else(|| t!("error.web_download", step = "Blob").into_owned())?;
 
     let a_tag: web_sys::HtmlAnchorElement = web_sys::window()
-        .ok_or("no global window")?
+        .ok_or_else(|| t!("error.web_download", step = "window").into_owned())?
         .document()
-        .ok_or("no \"window.document\"")?
+        .ok_or_else(|| t!("error.web_download", step = "window.document").into_owned())?
         .create_element("a")
-        .map_err(|_| "failed to create \"a\" tag")?
+        .map_err(|_| t!("error.web_download", step = "<a>").into_owned())?
         .unchecked_into();
 
     if let Some(file_name) = file_name {
@@ -733,7 +1353,7 @@ fn save_file_on_web_target(data: &[u8], file_name: Option<&str>) -> Result<(), S
 
     let url = web_sys::Url::create_object_url_with_blob(&blob)
         .ok()
-        .ok_or("url creation failed")?;
+        .ok_or_else(|| t!("error.web_download", step = "URL.createObjectURL").into_owned())?;
 
     a_tag.set_href(&url);
 
@@ -741,7 +1361,93 @@ fn save_file_on_web_target(data: &[u8], file_name: Option<&str>) -> Result<(), S
 
     web_sys::Url::revoke_object_url(&url)
         .ok()
-        .ok_or("url revoke failed")?;
+        .ok_or_else(|| t!("error.web_download", step = "URL.revokeObjectURL").into_owned())?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(index: u32, name: &str, urls: &[&str]) -> TabGroup {
+        TabGroup {
+            index,
+            name: name.to_owned(),
+            tabs: urls
+                .iter()
+                .zip(0..)
+                .map(|(url, index)| TabInfo {
+                    index,
+                    title: String::new(),
+                    url: (*url).to_owned(),
+                    parent: None,
+                    last_accessed: None,
+                    pinned: false,
+                })
+                .collect(),
+            closed_at: None,
+        }
+    }
+
+    fn session(open: Vec<TabGroup>) -> AllTabGroups {
+        AllTabGroups {
+            open,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn selection_follows_moved_windows_and_tabs() {
+        let old = session(vec![
+            group(0, "Window 1", &["a", "b", "c"]),
+            group(1, "Window 2", &["x", "y"]),
+        ]);
+        // A window was opened before the others and a tab was inserted:
+        let new = session(vec![
+            group(0, "Window 1", &["new"]),
+            group(1, "Window 2", &["a", "inserted", "b", "c"]),
+            group(2, "Window 3", &["x", "y"]),
+        ]);
+        let options = GenerateOptions {
+            open_group_indexes: Some(vec![0, 1]),
+            open_tab_indexes: BTreeMap::from([(0, vec![1, 2])]),
+            ..Default::default()
+        };
+
+        let changed = SessionChanges::new(&old, &new).apply(&options);
+        assert_eq!(changed.open_group_indexes, Some(vec![1, 2]));
+        assert_eq!(changed.open_tab_indexes, BTreeMap::from([(1, vec![2, 3])]));
+    }
+
+    #[test]
+    fn removed_windows_and_tabs_are_deselected() {
+        let old = session(vec![
+            group(0, "Window 1", &["a", "b"]),
+            group(1, "Window 2", &["x"]),
+        ]);
+        let new = session(vec![group(0, "Window 1", &["a"])]);
+        let options = GenerateOptions {
+            open_group_indexes: Some(vec![0, 1]),
+            open_tab_indexes: BTreeMap::from([(0, vec![0, 1])]),
+            ..Default::default()
+        };
+
+        let changes = SessionChanges::new(&old, &new);
+        assert_eq!(changes.group(true, 1), None);
+        assert_eq!(changes.tab(true, 0, 1), None);
+        let changed = changes.apply(&options);
+        assert_eq!(changed.open_group_indexes, Some(vec![0]));
+        assert_eq!(changed.open_tab_indexes, BTreeMap::from([(0, vec![0])]));
+    }
+
+    #[test]
+    fn keeping_all_windows_selected() {
+        let old = session(vec![group(0, "Window 1", &["a"])]);
+        let new = session(vec![group(0, "Window 1", &["b"])]);
+        let options = GenerateOptions::default();
+
+        let changed = SessionChanges::new(&old, &new).apply(&options);
+        assert_eq!(changed, options);
+    }
+}
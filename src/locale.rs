@@ -0,0 +1,54 @@
+//! Selects the language of the user interface. Translated messages are stored
+//! in `locales/app.yml` and looked up using [`rust_i18n::t`].
+
+use rust_i18n::t;
+
+use crate::host::FormatInfo;
+
+/// Languages that the user interface is translated to, as locale codes and
+/// the name of each language in that language.
+pub const LANGUAGES: &[(&str, &str)] = &[("en", "English"), ("de", "Deutsch")];
+
+/// Name of a supported language in that language.
+pub fn language_name(code: &str) -> &str {
+    LANGUAGES
+        .iter()
+        .find(|(supported, _)| *supported == code)
+        .map_or(code, |(_, name)| name)
+}
+
+/// Find the supported language that best matches a locale such as `de-DE`.
+fn supported_language(locale: &str) -> Option<&'static str> {
+    let language = locale.split(['-', '_', '.']).next()?;
+    LANGUAGES
+        .iter()
+        .map(|(code, _)| *code)
+        .find(|code| code.eq_ignore_ascii_case(language))
+}
+
+/// Change the language of the user interface. The system language is used
+/// if `language` is `None`, and English if neither is supported.
+pub fn set_language(language: Option<&str>) {
+    let code = language
+        .and_then(supported_language)
+        .or_else(|| {
+            sys_locale::get_locale()
+                .as_deref()
+                .and_then(supported_language)
+        })
+        .unwrap_or("en");
+    rust_i18n::set_locale(code);
+}
+
+/// Markdown description of a builtin output format in the current language.
+pub fn format_description(format: FormatInfo) -> String {
+    let key = format!("format.{}", format.as_str());
+    let text = t!(&key);
+    if text == key {
+        // No translation for this format, use the description from
+        // `firefox_session_data`:
+        format.to_string()
+    } else {
+        text.into_owned()
+    }
+}
@@ -1,30 +1,56 @@
 #![cfg_attr(all(windows, not(debug_assertions)), windows_subsystem = "windows")]
 
+mod actions;
 mod elm;
+mod error_report;
+mod history;
 mod host;
+mod locale;
+mod script;
+mod settings;
+mod status_log;
+mod template;
 
-use crate::elm::{MsgSender, Update};
+// Translations for `t!`, loaded from `locales/app.yml`:
+rust_i18n::i18n!("locales", fallback = "en");
+
+use crate::elm::subscription::Sub;
+use crate::elm::trace::{self, TraceEntry};
+use crate::elm::{Effect, Message, MsgSender, TaskId, Update};
+use crate::error_report::{ErrorReport, FailedTask};
+use crate::history::History;
+use crate::status_log::{Severity, StatusLog};
 use gpui::{
-    div, prelude::*, px, AlignItems, AnyView, App, AppContext, Application, AssetSource,
-    ClipboardItem, Entity, Pixels, SharedString, Size, StyleRefinement, WeakEntity, Window,
+    div, img, prelude::*, px, uniform_list, AlignItems, AnyView, App, AppContext, Application,
+    AssetSource, ClipboardItem, Entity, ExternalPaths, FocusHandle, Image, ImageFormat,
+    MouseButton, Pixels, SharedString, Size, StyleRefinement, Subscription, WeakEntity, Window,
     WindowOptions,
 };
 use gpui_component::{
-    button::Button,
+    button::{Button, ButtonVariants},
     checkbox::Checkbox,
-    dropdown::{Dropdown, DropdownItem, DropdownState},
+    dropdown::{Dropdown, DropdownEvent, DropdownItem, DropdownState},
     group_box::GroupBox,
     h_flex,
-    input::{InputState, TextInput},
+    input::{InputEvent, InputState, TextInput},
     label::Label,
     list::{List, ListDelegate, ListItem},
+    notification::{Notification, NotificationType},
     text::TextView,
     tooltip::Tooltip,
-    v_flex, ContextModal, Icon, IconName, IndexPath, Root, StyledExt,
+    v_flex, ActiveTheme, ContextModal, Disableable, Icon, IconName, IndexPath, Root, Sizable,
+    StyledExt, Theme, ThemeMode,
 };
 use rust_embed::RustEmbed;
+use rust_i18n::t;
+use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
-use std::path::PathBuf;
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
 /// An asset source that loads assets from the `./assets` folder.
 #[derive(RustEmbed)]
@@ -53,13 +79,44 @@ impl AssetSource for Assets {
 struct WizardList {
     parent: WeakEntity<FirefoxSessionUtility>,
     found_profiles: Vec<host::FirefoxProfileInfo>,
+    /// Indexes of profiles whose name matches the search query.
+    matched_profiles: Vec<usize>,
     selected_index: Option<gpui_component::IndexPath>,
 }
+impl WizardList {
+    fn set_profiles(&mut self, profiles: Vec<host::FirefoxProfileInfo>) {
+        self.matched_profiles = (0..profiles.len()).collect();
+        self.found_profiles = profiles;
+    }
+
+    fn profile(&self, ix: gpui_component::IndexPath) -> Option<&host::FirefoxProfileInfo> {
+        self.found_profiles.get(*self.matched_profiles.get(ix.row)?)
+    }
+}
 impl ListDelegate for WizardList {
     type Item = ListItem;
 
     fn items_count(&self, _section: usize, _cx: &App) -> usize {
-        self.found_profiles.len()
+        self.matched_profiles.len()
+    }
+
+    fn perform_search(
+        &mut self,
+        query: &str,
+        _window: &mut Window,
+        cx: &mut Context<gpui_component::List<Self>>,
+    ) -> gpui::Task<()> {
+        let query = query.to_lowercase();
+        self.matched_profiles = self
+            .found_profiles
+            .iter()
+            .enumerate()
+            .filter(|(_, profile)| profile.name().to_lowercase().contains(&query))
+            .map(|(ix, _)| ix)
+            .collect();
+        self.selected_index = None;
+        cx.notify();
+        gpui::Task::ready(())
     }
 
     fn render_item(
@@ -68,7 +125,7 @@ impl ListDelegate for WizardList {
         _window: &mut Window,
         _cx: &mut Context<gpui_component::List<Self>>,
     ) -> Option<Self::Item> {
-        self.found_profiles.get(ix.row).map(|item| {
+        self.profile(ix).map(|item| {
             ListItem::new(ix)
                 .child(Label::new(item.name().into_owned()))
                 .selected(Some(ix) == self.selected_index)
@@ -85,7 +142,7 @@ impl ListDelegate for WizardList {
         cx.notify();
 
         let Some(ix) = ix else { return };
-        let Some(selected) = self.found_profiles.get(ix.row) else {
+        let Some(selected) = self.profile(ix) else {
             return;
         };
         let selected = selected
@@ -95,8 +152,8 @@ impl ListDelegate for WizardList {
 
         if let Some(parent) = self.parent.upgrade() {
             parent.update(cx, |parent, cx| {
-                parent.update(window, cx, Command::SetInputPath(selected, None));
-                parent.update(window, cx, Command::LoadNewInputData);
+                parent.dispatch(window, cx, Command::SetInputPath(selected, None));
+                parent.dispatch(window, cx, Command::LoadNewInputData);
             })
         }
         window.close_modal(cx);
@@ -117,12 +174,12 @@ impl Wizard {
                 WizardList {
                     parent,
                     found_profiles: Vec::new(),
+                    matched_profiles: Vec::new(),
                     selected_index: None,
                 },
                 window,
                 cx,
             )
-            .no_query()
         });
         Wizard { list }
     }
@@ -131,22 +188,28 @@ impl Wizard {
             return;
         };
         list.update(cx, |view, _cx| {
-            view.delegate_mut().found_profiles = host::FirefoxProfileInfo::all_profiles();
+            view.delegate_mut()
+                .set_profiles(host::FirefoxProfileInfo::all_profiles());
         });
         window.open_modal(cx, move |modal, _window, _cx| {
             modal
                 .my_10()
-                .title("Select Firefox Session Data")
+                .title(t!("wizard.title").to_string())
                 .child(
                     v_flex()
-                        .child("Firefox Profiles:")
+                        .child(t!("wizard.profiles").to_string())
                         .child(v_flex().child(list.clone()).h_64())
-                        .child(Button::new("cancel").mt_8().label("Cancel").on_click({
-                            move |_, window, cx| {
-                                eprintln!("Modal closed via button");
-                                window.close_modal(cx);
-                            }
-                        })),
+                        .child(
+                            Button::new("cancel")
+                                .mt_8()
+                                .label(t!("common.cancel").to_string())
+                                .on_click({
+                                    move |_, window, cx| {
+                                        eprintln!("Modal closed via button");
+                                        window.close_modal(cx);
+                                    }
+                                }),
+                        ),
                 )
                 .on_close(|_, _, _| {
                     eprintln!("Modal closed");
@@ -155,312 +218,2040 @@ impl Wizard {
     }
 }
 
-/// A view of an output format.
-#[derive(Clone, Copy, gpui::IntoElement)]
-pub struct FormatInfoValue(pub host::FormatInfo);
-impl DropdownItem for FormatInfoValue {
-    type Value = host::FormatInfo;
-
-    fn title(&self) -> SharedString {
-        self.0.as_str().into()
-    }
+/// Where a tab that was found by the tab search is located.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum TabLocation {
+    /// A tab in an open or closed window, shown in the sidebar.
+    Window(TreeNode),
+    /// A recently closed tab.
+    ClosedTab,
+}
 
-    fn value(&self) -> &Self::Value {
-        &self.0
-    }
+/// A tab that is searched by the tab search panel.
+#[derive(Debug, Clone)]
+struct SearchableTab {
+    location: TabLocation,
+    window: String,
+    title: String,
+    url: String,
+}
 
-    fn display_title(&self) -> Option<gpui::AnyElement> {
-        Some(gpui::IntoElement::into_any_element(*self))
-    }
+struct TabSearchList {
+    parent: WeakEntity<FirefoxSessionUtility>,
+    tabs: Vec<SearchableTab>,
+    /// Indexes of tabs whose title or URL matches the search query.
+    matched_tabs: Vec<usize>,
+    selected_index: Option<IndexPath>,
 }
-impl gpui::RenderOnce for FormatInfoValue {
-    fn render(self, _window: &mut Window, _cx: &mut App) -> impl IntoElement {
-        div()
-            .size_full()
-            .child(self.0.as_str())
-            .id(SharedString::from(format!(
-                "{}-output-format-option",
-                self.0.as_str()
-            )))
-            .tooltip({
-                let info = self.0;
-                move |window, cx| {
-                    Tooltip::element(move |window, cx| {
-                        TextView::markdown(info.as_str(), info.to_string(), window, cx)
+impl TabSearchList {
+    fn set_tab_groups(&mut self, tab_groups: &host::AllTabGroups) {
+        let windows = [(true, &tab_groups.open), (false, &tab_groups.closed)]
+            .into_iter()
+            .flat_map(|(open, groups)| {
+                groups.iter().flat_map(move |group| {
+                    group.tabs.iter().map(move |tab| SearchableTab {
+                        location: TabLocation::Window(TreeNode {
+                            open,
+                            group: group.index,
+                            tab: Some(tab.index),
+                        }),
+                        window: group.name.clone(),
+                        title: tab.title.clone(),
+                        url: tab.url.clone(),
                     })
-                    .build(window, cx)
-                }
-            })
+                })
+            });
+        let closed_tabs = tab_groups.closed_tabs.iter().map(|closed| SearchableTab {
+            location: TabLocation::ClosedTab,
+            window: t!("tab_search.closed_tab_in", window = closed.window).to_string(),
+            title: closed.tab.title.clone(),
+            url: closed.tab.url.clone(),
+        });
+        self.tabs = windows.chain(closed_tabs).collect();
+        self.matched_tabs = (0..self.tabs.len()).collect();
+        self.selected_index = None;
     }
-}
 
-#[derive(Clone)]
-pub struct TabGroupList {
-    parent: WeakEntity<FirefoxSessionUtility>,
-    tab_groups: host::AllTabGroups,
-    selected_tab_groups: host::GenerateOptions,
-    /// Most recently selected list item.
-    selected_item: Option<IndexPath>,
-}
-impl TabGroupList {
-    fn change_selected_tab_group(&mut self, index: u32, open: bool, select: bool) -> bool {
-        let (mut indexes, mut other) = (
-            &mut self.selected_tab_groups.open_group_indexes,
-            &mut self.selected_tab_groups.closed_group_indexes,
-        );
-        if !open {
-            std::mem::swap(&mut indexes, &mut other);
-        }
-        if select {
-            let indexes = indexes.get_or_insert_with(Vec::new);
-            other.get_or_insert_with(Vec::new);
-            if !indexes.contains(&index) {
-                indexes.push(index);
-                true // regen
-            } else {
-                false // already selected
-            }
-        } else if let Some(indexes) = indexes {
-            let len = indexes.len();
-            indexes.retain(|v| *v != index);
-            if indexes.len() != len {
-                // Something was removed => update preview:
-                if self.selected_tab_groups.selected_groups() == 0 {
-                    // Nothing selected => select all open windows:
-                    self.selected_tab_groups.open_group_indexes = None;
-                    self.selected_tab_groups
-                        .closed_group_indexes
-                        .get_or_insert_with(Vec::new);
-                }
-                true // regen
-            } else {
-                false
-            }
-        } else {
-            false // nothing to deselect
-        }
+    fn selected_tab(&self) -> Option<&SearchableTab> {
+        let ix = self.selected_index?;
+        self.tabs.get(*self.matched_tabs.get(ix.row)?)
     }
 }
-impl ListDelegate for TabGroupList {
+impl ListDelegate for TabSearchList {
     type Item = ListItem;
 
-    fn sections_count(&self, _cx: &App) -> usize {
-        2 // open and closed
+    fn items_count(&self, _section: usize, _cx: &App) -> usize {
+        self.matched_tabs.len()
     }
 
-    fn items_count(&self, section: usize, _cx: &App) -> usize {
-        match section {
-            0 => self.tab_groups.open.len(),
-            1 => self.tab_groups.closed.len(),
-            _ => 0,
-        }
+    fn perform_search(
+        &mut self,
+        query: &str,
+        _window: &mut Window,
+        cx: &mut Context<List<Self>>,
+    ) -> gpui::Task<()> {
+        let query = query.trim().to_lowercase();
+        self.matched_tabs = self
+            .tabs
+            .iter()
+            .enumerate()
+            .filter(|(_, tab)| {
+                tab.title.to_lowercase().contains(&query) || tab.url.to_lowercase().contains(&query)
+            })
+            .map(|(ix, _)| ix)
+            .collect();
+        self.selected_index = None;
+        cx.notify();
+        gpui::Task::ready(())
     }
 
     fn render_item(
         &self,
         ix: IndexPath,
         _window: &mut Window,
-        _cx: &mut Context<'_, List<Self>>,
+        _cx: &mut Context<List<Self>>,
     ) -> Option<Self::Item> {
-        let (groups, selected_indexes) = match ix.section {
-            0 => (
-                &self.tab_groups.open,
-                &self.selected_tab_groups.open_group_indexes,
-            ),
-            1 => (
-                &self.tab_groups.closed,
-                &self.selected_tab_groups.closed_group_indexes,
-            ),
-            _ => return None,
-        };
-        groups.get(ix.row).map(|item| {
-            let is_selected = selected_indexes
-                .as_ref()
-                .is_some_and(|indexes| indexes.contains(&item.index));
-            ListItem::new(ix)
-                .child(Label::new(item.name.clone()))
-                .check_icon(IconName::Check)
-                .confirmed(is_selected)
-                .selected(is_selected)
-        })
-    }
-
-    fn render_section_header(
-        &self,
-        section: usize,
-        _window: &mut Window,
-        _cx: &mut Context<'_, List<Self>>,
-    ) -> Option<impl IntoElement> {
-        let title = match section {
-            0 => "Open Windows",
-            1 => "Closed Windows",
-            _ => return None,
-        };
-
+        let tab = self.tabs.get(*self.matched_tabs.get(ix.row)?)?;
         Some(
-            h_flex()
-                .px_2()
-                .py_1()
-                .gap_2()
-                .text_sm()
-                // .text_color(cx.theme().muted_foreground)
-                .child(Icon::new(IconName::Folder))
-                .child(title),
+            ListItem::new(ix)
+                .child(
+                    v_flex()
+                        .child(Label::new(tab.title.clone()).truncate())
+                        .child(
+                            h_flex()
+                                .gap_2()
+                                .text_xs()
+                                .child(Label::new(tab.window.clone()))
+                                .child(Label::new(tab.url.clone()).truncate()),
+                        ),
+                )
+                .selected(Some(ix) == self.selected_index),
         )
     }
 
-    fn render_section_footer(
-        &self,
-        _section: usize,
-        _window: &mut Window,
-        _cx: &mut Context<'_, List<Self>>,
-    ) -> Option<impl IntoElement> {
-        Some(div().px_2().py_1().child(""))
-    }
-
     fn set_selected_index(
         &mut self,
         ix: Option<IndexPath>,
         _window: &mut Window,
-        _cx: &mut Context<List<Self>>,
+        cx: &mut Context<List<Self>>,
     ) {
-        self.selected_item = ix;
+        self.selected_index = ix;
+        cx.notify();
     }
 
     fn confirm(&mut self, _secondary: bool, window: &mut Window, cx: &mut Context<List<Self>>) {
-        let Some(ix) = self.selected_item else { return };
-        let selected_indexes = match ix.section {
-            0 => &self.selected_tab_groups.open_group_indexes,
-            1 => &self.selected_tab_groups.closed_group_indexes,
-            _ => return,
+        let Some(tab) = self.selected_tab() else {
+            return;
         };
-        let was_selected = selected_indexes
-            .as_ref()
-            .is_some_and(|indexes| indexes.contains(&(ix.row as u32)));
-
-        if self.change_selected_tab_group(ix.row as u32, ix.section == 0, !was_selected) {
-            let parent = self.parent.clone();
-            MsgSender::new(window.to_async(cx), parent)
-                .spawn(async move |_window, mut sender| {
-                    sender.send(Command::RegeneratePreview);
-                })
-                .detach();
+        let location = tab.location;
+        if let Some(parent) = self.parent.upgrade() {
+            parent.update(cx, |parent, cx| {
+                parent.dispatch(window, cx, Command::RevealTab(location));
+            })
         }
+        window.close_modal(cx);
+    }
+}
 
-        cx.notify();
+/// Modal used to search all tabs in the loaded session.
+struct TabSearch {
+    list: Entity<List<TabSearchList>>,
+}
+impl TabSearch {
+    fn new(
+        window: &mut Window,
+        cx: &mut Context<TabSearch>,
+        parent: WeakEntity<FirefoxSessionUtility>,
+    ) -> Self {
+        let list = cx.new(|cx| {
+            List::new(
+                TabSearchList {
+                    parent,
+                    tabs: Vec::new(),
+                    matched_tabs: Vec::new(),
+                    selected_index: None,
+                },
+                window,
+                cx,
+            )
+        });
+        TabSearch { list }
+    }
+    fn open_modal(
+        window: &mut Window,
+        cx: &mut App,
+        view: WeakEntity<TabSearch>,
+        tab_groups: &host::AllTabGroups,
+    ) {
+        let Ok(list) = view.read_with(cx, |search, _| search.list.clone()) else {
+            return;
+        };
+        list.update(cx, |list, cx| {
+            list.delegate_mut().set_tab_groups(tab_groups);
+            cx.notify();
+        });
+        window.open_modal(cx, move |modal, _window, cx| {
+            let selected = list.read(cx).delegate().selected_tab().cloned();
+            let in_window = selected.as_ref().and_then(|tab| match tab.location {
+                TabLocation::Window(node) => Some(node),
+                TabLocation::ClosedTab => None,
+            });
+            let parent = list.read(cx).delegate().parent.clone();
+            let send = move |command: Command| {
+                let parent = parent.clone();
+                move |_: &gpui::ClickEvent, window: &mut Window, cx: &mut App| {
+                    _ = parent.update(cx, |parent, cx| {
+                        parent.dispatch(window, cx, command.clone());
+                    });
+                }
+            };
+
+            modal
+                .title(t!("tab_search.title").to_string())
+                .width(px(700.))
+                .child(
+                    v_flex()
+                        .gap_2()
+                        .child(v_flex().child(list.clone()).h_96())
+                        .child(
+                            h_flex()
+                                .gap_2()
+                                .child(
+                                    Button::new("tab-search-reveal")
+                                        .label(t!("tab_search.reveal").to_string())
+                                        .disabled(in_window.is_none())
+                                        .when_some(in_window, |this, node| {
+                                            let reveal =
+                                                send(Command::RevealTab(TabLocation::Window(node)));
+                                            this.on_click(move |event, window, cx| {
+                                                reveal(event, window, cx);
+                                                window.close_modal(cx);
+                                            })
+                                        }),
+                                )
+                                .child(
+                                    Button::new("tab-search-select")
+                                        .label(t!("tab_search.select").to_string())
+                                        .disabled(in_window.is_none())
+                                        .when_some(
+                                            in_window.and_then(|node| Some((node, node.tab?))),
+                                            |this, (node, tab)| {
+                                                this.on_click(send(Command::ChangeTabSelection {
+                                                    open: node.open,
+                                                    group: node.group,
+                                                    tab,
+                                                    select: true,
+                                                }))
+                                            },
+                                        ),
+                                )
+                                .child(
+                                    Button::new("tab-search-open")
+                                        .label(t!("common.open_in_browser").to_string())
+                                        .disabled(selected.is_none())
+                                        .when_some(selected.as_ref(), |this, tab| {
+                                            this.on_click(send(Command::OpenUrls(vec![tab
+                                                .url
+                                                .clone()])))
+                                        }),
+                                )
+                                .child(
+                                    Button::new("tab-search-copy")
+                                        .label(t!("tab_search.copy_url").to_string())
+                                        .disabled(selected.is_none())
+                                        .on_click({
+                                            let url = selected
+                                                .as_ref()
+                                                .map(|tab| tab.url.clone())
+                                                .unwrap_or_default();
+                                            move |_, _window, cx| {
+                                                cx.write_to_clipboard(ClipboardItem::new_string(
+                                                    url.clone(),
+                                                ));
+                                            }
+                                        }),
+                                )
+                                .child(div().flex_grow())
+                                .child(
+                                    Button::new("tab-search-close")
+                                        .label(t!("common.close").to_string())
+                                        .on_click(|_, window, cx| {
+                                            window.close_modal(cx);
+                                        }),
+                                ),
+                        ),
+                )
+        })
     }
 }
 
-#[derive(Clone)]
-pub enum Command {
-    SetInputPath(String, Option<rfd::FileHandle>),
-    LoadNewInputData,
-    UpdateLoadedData(host::FileInfo),
-    ParsedTabGroups(host::AllTabGroups),
-    RegeneratePreview,
-    SetPreview(String),
-    ChangeTabGroupSelection {
-        open: bool,
-        index: u32,
-        select: bool,
-    },
-    SetSavePath(String),
-    SetStatus(String),
-    SaveLinksToFile,
+/// Modal used to create and edit user defined output templates.
+struct TemplateEditor {
+    parent: WeakEntity<FirefoxSessionUtility>,
+    /// Name of the template that is being edited, `None` when creating a new
+    /// template.
+    editing: Option<String>,
+    name: Entity<InputState>,
+    file_extension: Entity<InputState>,
+    header: Entity<InputState>,
+    group: Entity<InputState>,
+    tab: Entity<InputState>,
+    footer: Entity<InputState>,
 }
-impl Update<Command> for FirefoxSessionUtility {
-    fn update(&mut self, window: &mut Window, cx: &mut Context<Self>, msg: Command) {
-        match msg {
-            Command::SetInputPath(input_path, data) => {
-                self.new_input_data = data;
-                self.new_input.update(cx, |new_input, cx| {
-                    new_input.set_value(input_path, window, cx);
-                })
+impl TemplateEditor {
+    fn new(
+        window: &mut Window,
+        cx: &mut Context<TemplateEditor>,
+        parent: WeakEntity<FirefoxSessionUtility>,
+    ) -> Self {
+        let mut multi_line = || cx.new(|cx| InputState::new(window, cx).multi_line());
+        let (header, group, tab, footer) = (multi_line(), multi_line(), multi_line(), multi_line());
+        TemplateEditor {
+            parent,
+            editing: None,
+            name: cx.new(|cx| InputState::new(window, cx)),
+            file_extension: cx.new(|cx| InputState::new(window, cx)),
+            header,
+            group,
+            tab,
+            footer,
+        }
+    }
+    fn template(&self, cx: &App) -> template::OutputTemplate {
+        let value = |input: &Entity<InputState>| input.read(cx).value().to_string();
+        template::OutputTemplate {
+            name: value(&self.name).trim().to_owned(),
+            file_extension: value(&self.file_extension)
+                .trim()
+                .trim_start_matches('.')
+                .to_owned(),
+            header: value(&self.header),
+            group: value(&self.group),
+            tab: value(&self.tab),
+            footer: value(&self.footer),
+        }
+    }
+    fn open_modal(
+        window: &mut Window,
+        cx: &mut App,
+        view: WeakEntity<TemplateEditor>,
+        template: Option<Arc<template::OutputTemplate>>,
+    ) {
+        let Some(editor) = view.upgrade() else {
+            return;
+        };
+        editor.update(cx, |editor, cx| {
+            editor.editing = template.as_ref().map(|template| template.name.clone());
+            let template = template.as_deref().cloned().unwrap_or_default();
+            for (input, value) in [
+                (&editor.name, template.name),
+                (&editor.file_extension, template.file_extension),
+                (&editor.header, template.header),
+                (&editor.group, template.group),
+                (&editor.tab, template.tab),
+                (&editor.footer, template.footer),
+            ] {
+                input.update(cx, |input, cx| input.set_value(value, window, cx));
             }
-            Command::LoadNewInputData => {
-                let input_path = self.new_input.read(cx).value();
-                self.loaded_input.update(cx, |loaded_input, cx| {
-                    loaded_input.set_value(input_path.clone(), window, cx);
-                });
-
-                let mut data = host::FileInfo::new(if let Some(data) = &self.new_input_data {
-                    data.path().to_owned()
+        });
+        window.open_modal(cx, move |modal, _window, cx| {
+            let editor = editor.read(cx);
+            let field = |label: Cow<'static, str>, input: &Entity<InputState>| {
+                v_flex()
+                    .gap_1()
+                    .child(label.into_owned())
+                    .child(TextInput::new(input))
+            };
+            modal
+                .title(if editor.editing.is_some() {
+                    t!("template.edit_title").to_string()
                 } else {
-                    PathBuf::from(input_path.as_str())
-                });
-                data.file_handle = self.new_input_data.clone();
-                self.loaded_input_data = Some(data.clone());
+                    t!("template.new_title").to_string()
+                })
+                .width(px(700.))
+                .child(
+                    v_flex()
+                        .gap_2()
+                        .child(t!("template.help").to_string())
+                        .child(
+                            h_flex()
+                                .gap_2()
+                                .child(field(t!("template.name"), &editor.name).flex_grow())
+                                .child(field(
+                                    t!("template.file_extension"),
+                                    &editor.file_extension,
+                                )),
+                        )
+                        .child(field(t!("template.header"), &editor.header))
+                        .child(field(t!("template.group"), &editor.group))
+                        .child(field(t!("template.tab"), &editor.tab))
+                        .child(field(t!("template.footer"), &editor.footer))
+                        .child(
+                            h_flex()
+                                .mt_4()
+                                .gap_2()
+                                .child(
+                                    Button::new("template-save")
+                                        .label(t!("template.save").to_string())
+                                        .on_click({
+                                            let view = view.clone();
+                                            move |_, window, cx| {
+                                                let Some(editor) = view.upgrade() else {
+                                                    return;
+                                                };
+                                                let editor = editor.read(cx);
+                                                let template = editor.template(cx);
+                                                if template.name.is_empty() {
+                                                    return;
+                                                }
+                                                let previous_name = editor.editing.clone();
+                                                if let Some(parent) = editor.parent.upgrade() {
+                                                    parent.update(cx, |parent, cx| {
+                                                        parent.dispatch(
+                                                            window,
+                                                            cx,
+                                                            Command::SaveTemplate {
+                                                                previous_name,
+                                                                template,
+                                                            },
+                                                        );
+                                                    });
+                                                }
+                                                window.close_modal(cx);
+                                            }
+                                        }),
+                                )
+                                .children(editor.editing.clone().map(|name| {
+                                    let parent = editor.parent.clone();
+                                    Button::new("template-delete")
+                                        .label(t!("template.delete").to_string())
+                                        .on_click(move |_, window, cx| {
+                                            if let Some(parent) = parent.upgrade() {
+                                                parent.update(cx, |parent, cx| {
+                                                    parent.dispatch(
+                                                        window,
+                                                        cx,
+                                                        Command::DeleteTemplate(name.clone()),
+                                                    );
+                                                });
+                                            }
+                                            window.close_modal(cx);
+                                        })
+                                }))
+                                .child(
+                                    Button::new("template-cancel")
+                                        .label(t!("common.cancel").to_string())
+                                        .on_click(|_, window, cx| {
+                                            window.close_modal(cx);
+                                        }),
+                                ),
+                        ),
+                )
+        })
+    }
+}
 
-                self.tab_group_list.update(cx, |tab_group_list, _cx| {
-                    tab_group_list
-                        .delegate_mut()
-                        .selected_tab_groups
-                        .open_group_indexes = None;
-                    tab_group_list
-                        .delegate_mut()
-                        .selected_tab_groups
-                        .closed_group_indexes = Some(Vec::new());
-                });
-                self.set_status(window, cx, "Reading input file");
+/// A previously loaded file that can be selected in the recent files dropdown.
+#[derive(Clone)]
+struct RecentFileValue {
+    file: settings::RecentFile,
+    /// When the file was last modified, read once when the dropdown's items
+    /// are created rather than every time they are rendered.
+    modified: Option<SystemTime>,
+}
+impl RecentFileValue {
+    fn new(file: settings::RecentFile) -> Self {
+        let modified = file.modified();
+        Self { file, modified }
+    }
+}
+impl DropdownItem for RecentFileValue {
+    type Value = String;
 
-                MsgSender::from_cx(window, cx)
-                    .spawn(async move |_window, mut sender| {
-                        if let Err(e) = data.load_data().await {
-                            sender.send(Command::SetStatus(format!("Failed to read file: {e}")));
-                            return;
-                        };
-                        sender.send(Command::UpdateLoadedData(data.clone()));
-                        loop {
-                            match &data.data {
-                                Some(host::FileData::Compressed { .. }) => {
-                                    sender
-                                        .send(Command::SetStatus("Decompressing data".to_string()));
-                                    if let Err(e) = data.decompress_data().await {
-                                        sender.send(Command::SetStatus(format!(
-                                            "Failed to decompress data: {e}"
-                                        )));
-                                        return;
-                                    }
-                                }
-                                Some(host::FileData::Uncompressed { .. }) => {
-                                    sender.send(Command::SetStatus(
-                                        "Parsing session data".to_string(),
-                                    ));
-                                    if let Err(e) = data.parse_session_data().await {
-                                        sender.send(Command::SetStatus(format!(
-                                            "Failed to parse session data: {e}"
-                                        )));
-                                        return;
-                                    }
-                                }
-                                Some(host::FileData::Parsed { .. }) => {
-                                    sender.send(match data.get_groups_from_session(true).await {
-                                        Ok(all_groups) => Command::ParsedTabGroups(all_groups),
-                                        Err(e) => Command::SetStatus(format!(
-                                            "Failed to list windows in session: {e}"
-                                        )),
-                                    });
-                                    return;
-                                }
-                                None => unreachable!("we just loaded the data"),
-                            }
-                            sender.send(Command::UpdateLoadedData(data.clone()));
-                        }
+    fn title(&self) -> SharedString {
+        match self.file.profile() {
+            Some(profile) => format!(
+                "{profile}: {}",
+                Path::new(&self.file.path)
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+            )
+            .into(),
+            None => self.file.path.clone().into(),
+        }
+    }
+
+    fn value(&self) -> &Self::Value {
+        &self.file.path
+    }
+
+    fn display_title(&self) -> Option<gpui::AnyElement> {
+        let modified = self
+            .modified
+            .map(|time| {
+                chrono::DateTime::<chrono::Local>::from(time)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            })
+            .unwrap_or_else(|| t!("recent.file_not_found").to_string());
+        let windows = match self.file.window_count {
+            1 => t!("count.windows_one").to_string(),
+            count => t!("count.windows_other", count = count).to_string(),
+        };
+        Some(
+            v_flex()
+                .child(self.title())
+                .child(div().text_xs().child(format!("{modified}, {windows}")))
+                .into_any_element(),
+        )
+    }
+}
+
+/// Modal that explains why something failed and how it might be fixed.
+struct ErrorDetails;
+impl ErrorDetails {
+    fn open_modal(
+        window: &mut Window,
+        cx: &mut App,
+        parent: WeakEntity<FirefoxSessionUtility>,
+        report: ErrorReport,
+    ) {
+        window.open_modal(cx, move |modal, _window, _cx| {
+            let section =
+                |title: Cow<'static, str>| Label::new(title.into_owned()).font_semibold().mt_2();
+            let alternative = report.alternative_file();
+            modal.title(report.title()).width(px(700.)).child(
+                v_flex()
+                    .gap_1()
+                    .child(section(t!("error_report.file")))
+                    .child(report.path.display().to_string())
+                    .child(section(t!("error_report.error")))
+                    .children(
+                        report
+                            .chain()
+                            .into_iter()
+                            .enumerate()
+                            .map(|(depth, error)| {
+                                div()
+                                    .pl(px(depth as f32 * 16.))
+                                    .font_family("monospace")
+                                    .text_sm()
+                                    .child(error.to_owned())
+                            }),
+                    )
+                    .when_some(report.suggestion(), |this, suggestion| {
+                        this.child(section(t!("error_report.suggestion")))
+                            .child(suggestion)
                     })
-                    .detach();
+                    .child(
+                        h_flex()
+                            .mt_4()
+                            .gap_2()
+                            .when_some(alternative, |this, file| {
+                                let parent = parent.clone();
+                                this.child(
+                                    Button::new("error-load-alternative")
+                                        .label(
+                                            t!(
+                                                "error_report.load_file",
+                                                file = file
+                                                    .file_name()
+                                                    .unwrap_or_default()
+                                                    .to_string_lossy()
+                                            )
+                                            .to_string(),
+                                        )
+                                        .on_click(move |_, window, cx| {
+                                            let path = file.to_string_lossy().into_owned();
+                                            _ = parent.update(cx, |parent, cx| {
+                                                parent.dispatch(
+                                                    window,
+                                                    cx,
+                                                    Command::SetInputPath(path, None),
+                                                );
+                                                parent.dispatch(
+                                                    window,
+                                                    cx,
+                                                    Command::LoadNewInputData,
+                                                );
+                                            });
+                                            window.close_modal(cx);
+                                        }),
+                                )
+                            })
+                            .child(
+                                Button::new("error-copy-details")
+                                    .label(t!("error_report.copy").to_string())
+                                    .on_click({
+                                        let text = report.to_text();
+                                        move |_, _window, cx| {
+                                            cx.write_to_clipboard(ClipboardItem::new_string(
+                                                text.clone(),
+                                            ));
+                                        }
+                                    }),
+                            )
+                            .child(div().flex_grow())
+                            .child(
+                                Button::new("error-details-close")
+                                    .label(t!("common.close").to_string())
+                                    .on_click(|_, window, cx| {
+                                        window.close_modal(cx);
+                                    }),
+                            ),
+                    ),
+            )
+        })
+    }
+}
+
+/// Modal that shows application settings.
+struct SettingsPanel;
+impl SettingsPanel {
+    fn open_modal(window: &mut Window, cx: &mut App, parent: WeakEntity<FirefoxSessionUtility>) {
+        window.open_modal(cx, move |modal, _window, cx| {
+            let Some(view) = parent.upgrade() else {
+                return modal;
+            };
+            let settings = settings::Settings::global(cx).clone();
+            let browser_command = view.read(cx).browser_command.clone();
+            let open_tabs_confirm_limit = view.read(cx).open_tabs_confirm_limit.clone();
+
+            // Change a setting and save the result:
+            let change = |f: fn(&mut settings::Settings, bool)| {
+                let view = view.downgrade();
+                move |checked: &bool, window: &mut Window, cx: &mut App| {
+                    let mut settings = settings::Settings::global(cx).clone();
+                    f(&mut settings, *checked);
+                    _ = view.update(cx, |view, cx| {
+                        view.dispatch(window, cx, Command::SetSettings(settings));
+                    });
+                }
+            };
+
+            // Change a setting that isn't a checkbox:
+            let change_with = |f: Box<dyn Fn(&mut settings::Settings)>| {
+                let view = view.downgrade();
+                move |_: &gpui::ClickEvent, window: &mut Window, cx: &mut App| {
+                    let mut settings = settings::Settings::global(cx).clone();
+                    f(&mut settings);
+                    _ = view.update(cx, |view, cx| {
+                        view.dispatch(window, cx, Command::SetSettings(settings));
+                    });
+                }
+            };
+
+            modal
+                .title(t!("settings.title").to_string())
+                .width(px(600.))
+                .child(
+                    v_flex()
+                        .gap_2()
+                        .child(
+                            h_flex()
+                                .gap_2()
+                                .child(Icon::new(IconName::Globe))
+                                .child(t!("settings.language").to_string())
+                                .children(
+                                    std::iter::once(None)
+                                        .chain(
+                                            locale::LANGUAGES.iter().map(|&(code, _)| Some(code)),
+                                        )
+                                        .map(|language| {
+                                            Button::new(SharedString::from(format!(
+                                                "settings-language-{}",
+                                                language.unwrap_or("system")
+                                            )))
+                                            .label(match language {
+                                                Some(code) => {
+                                                    locale::language_name(code).to_owned()
+                                                }
+                                                None => t!("settings.system_language").to_string(),
+                                            })
+                                            .small()
+                                            .selected(settings.language.as_deref() == language)
+                                            .on_click(change_with(Box::new(move |settings| {
+                                                settings.language = language.map(str::to_owned);
+                                            })))
+                                        }),
+                                ),
+                        )
+                        .child(
+                            h_flex()
+                                .gap_2()
+                                .child(Icon::new(IconName::Palette))
+                                .child(t!("settings.theme").to_string())
+                                .children(settings::ThemePreference::all().iter().map(|&theme| {
+                                    Button::new(SharedString::from(format!(
+                                        "settings-theme-{theme:?}"
+                                    )))
+                                    .label(theme.title())
+                                    .icon(theme_icon(theme))
+                                    .small()
+                                    .selected(settings.theme == theme)
+                                    .on_click(change_with(Box::new(move |settings| {
+                                        settings.theme = theme;
+                                    })))
+                                })),
+                        )
+                        .child(
+                            h_flex()
+                                .gap_2()
+                                .child(Icon::new(IconName::ALargeSmall))
+                                .child(t!("settings.font_size").to_string())
+                                .child(
+                                    Button::new("settings-font-smaller")
+                                        .icon(IconName::Minus)
+                                        .small()
+                                        .tooltip(t!("settings.font_smaller").to_string())
+                                        .on_click(change_with(Box::new(|settings| {
+                                            settings.change_font_size(-1.);
+                                        }))),
+                                )
+                                .child(format!("{} px", settings.font_size))
+                                .child(
+                                    Button::new("settings-font-larger")
+                                        .icon(IconName::Plus)
+                                        .small()
+                                        .tooltip(t!("settings.font_larger").to_string())
+                                        .on_click(change_with(Box::new(|settings| {
+                                            settings.change_font_size(1.);
+                                        }))),
+                                )
+                                .child(
+                                    Button::new("settings-font-reset")
+                                        .label(t!("settings.font_default").to_string())
+                                        .small()
+                                        .disabled(settings.font_size == settings::DEFAULT_FONT_SIZE)
+                                        .on_click(change_with(Box::new(|settings| {
+                                            settings.font_size = settings::DEFAULT_FONT_SIZE;
+                                        }))),
+                                )
+                                .mb_4(),
+                        )
+                        .child(
+                            Checkbox::new("settings-load-last-input")
+                                .label(t!("settings.load_last_input").to_string())
+                                .checked(settings.load_last_input)
+                                .on_click(change(|settings, checked| {
+                                    settings.load_last_input = checked;
+                                })),
+                        )
+                        .child(
+                            Checkbox::new("settings-remember-window-size")
+                                .label(t!("settings.remember_window_size").to_string())
+                                .checked(settings.remember_window_size)
+                                .on_click(change(|settings, checked| {
+                                    settings.remember_window_size = checked;
+                                })),
+                        )
+                        .child(
+                            Checkbox::new("settings-live-reload")
+                                .label(t!("settings.live_reload").to_string())
+                                .checked(settings.live_reload)
+                                .on_click(change(|settings, checked| {
+                                    settings.live_reload = checked;
+                                })),
+                        )
+                        .child(
+                            h_flex()
+                                .mt_4()
+                                .gap_2()
+                                .child(t!("settings.browser_command").to_string())
+                                .child(TextInput::new(&browser_command)),
+                        )
+                        .child(
+                            h_flex()
+                                .gap_2()
+                                .child(t!("settings.open_tabs_limit").to_string())
+                                .child(TextInput::new(&open_tabs_confirm_limit).w(px(80.))),
+                        )
+                        .child(
+                            h_flex()
+                                .mt_4()
+                                .gap_2()
+                                .child(t!("settings.stored_at").to_string())
+                                .child(
+                                    settings::Settings::path()
+                                        .map(|path| path.to_string_lossy().into_owned())
+                                        .unwrap_or_else(|| {
+                                            t!("settings.no_config_folder").to_string()
+                                        }),
+                                ),
+                        )
+                        .child(
+                            h_flex()
+                                .mt_4()
+                                .gap_2()
+                                .child(
+                                    Button::new("settings-reset")
+                                        .label(t!("settings.reset").to_string())
+                                        .on_click({
+                                            let view = view.downgrade();
+                                            move |_, window, cx| {
+                                                _ = view.update(cx, |view, cx| {
+                                                    view.dispatch(
+                                                        window,
+                                                        cx,
+                                                        Command::SetSettings(Default::default()),
+                                                    );
+                                                });
+                                            }
+                                        }),
+                                )
+                                .child(
+                                    Button::new("settings-close")
+                                        .label(t!("common.close").to_string())
+                                        .on_click(|_, window, cx| {
+                                            window.close_modal(cx);
+                                        }),
+                                ),
+                        ),
+                )
+        })
+    }
+}
+
+/// An output format that can be selected in the dropdown.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(into = "RecordedOutputFormat", try_from = "RecordedOutputFormat")]
+pub enum OutputFormat {
+    /// A format supported by `firefox_session_data`.
+    Builtin(host::FormatInfo),
+    /// A user defined format.
+    Template(Arc<template::OutputTemplate>),
+    /// A script that reopens windows.
+    Script(script::ScriptKind),
+}
+/// Formats are shown by their id in the inspector.
+impl std::fmt::Debug for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("OutputFormat").field(&self.id()).finish()
+    }
+}
+impl OutputFormat {
+    /// Identifies the format in the settings file. Unlike the title this
+    /// doesn't depend on the selected language.
+    fn id(&self) -> SharedString {
+        match self {
+            OutputFormat::Builtin(format) => format.as_str().into(),
+            // Prefixed so that templates can't be confused with builtin formats:
+            OutputFormat::Template(template) => format!("template.{}", template.name).into(),
+            OutputFormat::Script(kind) => format!("script.{}", kind.file_extension()).into(),
+        }
+    }
+    fn title(&self) -> SharedString {
+        match self {
+            OutputFormat::Builtin(format) => format.as_str().into(),
+            OutputFormat::Template(template) => template.name.clone().into(),
+            OutputFormat::Script(kind) => kind.title().into(),
+        }
+    }
+    /// Markdown description of the format.
+    fn description(&self) -> SharedString {
+        match self {
+            OutputFormat::Builtin(format) => locale::format_description(*format).into(),
+            OutputFormat::Template(template) => t!(
+                "format.template",
+                name = template.name,
+                extension = template.file_extension
+            )
+            .to_string()
+            .into(),
+            OutputFormat::Script(kind) => kind.description().into(),
+        }
+    }
+    fn as_builtin(&self) -> Option<host::FormatInfo> {
+        match self {
+            OutputFormat::Builtin(format) => Some(*format),
+            OutputFormat::Template(_) | OutputFormat::Script(_) => None,
+        }
+    }
+    fn as_template(&self) -> Option<&Arc<template::OutputTemplate>> {
+        match self {
+            OutputFormat::Builtin(_) | OutputFormat::Script(_) => None,
+            OutputFormat::Template(template) => Some(template),
+        }
+    }
+}
+
+/// How an [`OutputFormat`] is stored in recordings of messages. Builtin
+/// formats are stored by name since they can't be serialized directly.
+#[derive(Clone, Serialize, Deserialize)]
+enum RecordedOutputFormat {
+    Builtin(String),
+    Template(template::OutputTemplate),
+    Script(script::ScriptKind),
+}
+impl From<OutputFormat> for RecordedOutputFormat {
+    fn from(format: OutputFormat) -> Self {
+        match format {
+            OutputFormat::Builtin(format) => Self::Builtin(format.as_str().to_owned()),
+            OutputFormat::Template(template) => Self::Template((*template).clone()),
+            OutputFormat::Script(kind) => Self::Script(kind),
+        }
+    }
+}
+impl TryFrom<RecordedOutputFormat> for OutputFormat {
+    type Error = String;
+
+    fn try_from(format: RecordedOutputFormat) -> Result<Self, Self::Error> {
+        Ok(match format {
+            RecordedOutputFormat::Builtin(name) => host::FormatInfo::all()
+                .iter()
+                .copied()
+                .find(|format| format.as_str() == name)
+                .map(OutputFormat::Builtin)
+                .ok_or_else(|| format!("unknown output format: {name}"))?,
+            RecordedOutputFormat::Template(template) => OutputFormat::Template(Arc::new(template)),
+            RecordedOutputFormat::Script(kind) => OutputFormat::Script(kind),
+        })
+    }
+}
+
+/// A view of an output format.
+#[derive(Clone, gpui::IntoElement)]
+pub struct FormatInfoValue(pub OutputFormat);
+impl DropdownItem for FormatInfoValue {
+    type Value = OutputFormat;
+
+    fn title(&self) -> SharedString {
+        self.0.title()
+    }
+
+    fn value(&self) -> &Self::Value {
+        &self.0
+    }
+
+    fn display_title(&self) -> Option<gpui::AnyElement> {
+        Some(gpui::IntoElement::into_any_element(self.clone()))
+    }
+}
+impl gpui::RenderOnce for FormatInfoValue {
+    fn render(self, _window: &mut Window, _cx: &mut App) -> impl IntoElement {
+        let title = self.0.title();
+        div()
+            .size_full()
+            .child(title.clone())
+            .id(SharedString::from(format!(
+                "{}{title}-output-format-option",
+                if self.0.as_template().is_some() {
+                    "template-"
+                } else {
+                    ""
+                }
+            )))
+            .tooltip({
+                let info = self.0.description();
+                move |window, cx| {
+                    Tooltip::element({
+                        let (title, info) = (title.clone(), info.clone());
+                        move |window, cx| {
+                            TextView::markdown(title.clone(), info.clone(), window, cx)
+                        }
+                    })
+                    .build(window, cx)
+                }
+            })
+    }
+}
+
+/// Identifies a window or a tab in the sidebar's tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+struct TreeNode {
+    open: bool,
+    group: u32,
+    tab: Option<u32>,
+}
+
+/// A visible row in the sidebar's tree.
+#[derive(Debug, Clone, Copy)]
+struct TreeRow {
+    node: TreeNode,
+    depth: usize,
+}
+
+/// How much of a tab group is included in the output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GroupSelection {
+    None,
+    Partial,
+    All,
+}
+
+/// Change the selection of many windows at once.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BulkSelection {
+    All,
+    None,
+    /// Select tabs that weren't selected and deselect the rest.
+    Invert,
+    /// Select every closed window in addition to the current selection.
+    AllClosed,
+    /// Select windows whose name or tabs contain some text (ignoring case)
+    /// and deselect all other windows.
+    Matching(String),
+}
+
+#[derive(Clone)]
+pub struct TabGroupList {
+    parent: WeakEntity<FirefoxSessionUtility>,
+    tab_groups: host::AllTabGroups,
+    selected_tab_groups: host::GenerateOptions,
+    /// Most recently selected list item.
+    selected_item: Option<IndexPath>,
+    /// Most recently toggled list item, used as the start of shift-click
+    /// range selections.
+    anchor_item: Option<IndexPath>,
+    /// Windows and tabs whose children are shown.
+    expanded: HashSet<TreeNode>,
+    /// Visible rows for open and closed windows.
+    rows: [Vec<TreeRow>; 2],
+    /// Only show windows whose name contains this text (in lowercase).
+    query: String,
+    /// Also show windows that have tabs whose title or URL matches the query.
+    search_tabs: bool,
+}
+impl TabGroupList {
+    fn groups(&self, open: bool) -> &[host::TabGroup] {
+        if open {
+            &self.tab_groups.open
+        } else {
+            &self.tab_groups.closed
+        }
+    }
+
+    fn set_tab_groups(&mut self, tab_groups: host::AllTabGroups) {
+        self.tab_groups = tab_groups;
+        self.anchor_item = None;
+        self.expanded.clear();
+        self.update_rows();
+    }
+
+    /// Replace the tab groups with a newer version of the same session. The
+    /// selected and expanded windows and tabs are kept if they still exist.
+    fn reload_tab_groups(&mut self, tab_groups: host::AllTabGroups) -> host::SessionChanges {
+        let changes = host::SessionChanges::new(&self.tab_groups, &tab_groups);
+        self.selected_tab_groups = changes.apply(&self.selected_tab_groups);
+        self.expanded = self
+            .expanded
+            .iter()
+            .filter_map(|node| {
+                Some(TreeNode {
+                    open: node.open,
+                    group: changes.group(node.open, node.group)?,
+                    tab: match node.tab {
+                        Some(tab) => Some(changes.tab(node.open, node.group, tab)?),
+                        None => None,
+                    },
+                })
+            })
+            .collect();
+        // Row indexes change when windows and tabs move:
+        self.selected_item = None;
+        self.anchor_item = None;
+        self.tab_groups = tab_groups;
+        self.update_rows();
+        changes
+    }
+
+    /// Replace the tab groups with the same groups that have different tab
+    /// parents. Expanded windows stay expanded.
+    fn update_tab_tree(&mut self, tab_groups: host::AllTabGroups) {
+        self.tab_groups = tab_groups;
+        self.update_rows();
+    }
+
+    fn set_search_tabs(&mut self, search_tabs: bool) {
+        self.search_tabs = search_tabs;
+        self.update_rows();
+    }
+
+    fn toggle_expanded(&mut self, node: TreeNode) {
+        if !self.expanded.remove(&node) {
+            self.expanded.insert(node);
+        }
+        self.update_rows();
+    }
+
+    /// Flatten the expanded parts of the tab trees into rows.
+    fn update_rows(&mut self) {
+        fn push_tabs(
+            rows: &mut Vec<TreeRow>,
+            expanded: &HashSet<TreeNode>,
+            group: &host::TabGroup,
+            open: bool,
+            parent: Option<u32>,
+            depth: usize,
+        ) {
+            for tab in &group.tabs {
+                let is_child = if parent.is_some() {
+                    tab.parent == parent
+                } else {
+                    // Tabs whose parent is missing are shown at the root:
+                    tab.parent
+                        .is_none_or(|parent| !group.tabs.iter().any(|tab| tab.index == parent))
+                };
+                if !is_child {
+                    continue;
+                }
+                let node = TreeNode {
+                    open,
+                    group: group.index,
+                    tab: Some(tab.index),
+                };
+                rows.push(TreeRow { node, depth });
+                if expanded.contains(&node) {
+                    push_tabs(rows, expanded, group, open, Some(tab.index), depth + 1);
+                }
+            }
+        }
+
+        for (section, open) in [(0, true), (1, false)] {
+            let groups = if open {
+                &self.tab_groups.open
+            } else {
+                &self.tab_groups.closed
+            };
+            let rows = &mut self.rows[section];
+            rows.clear();
+            for group in groups {
+                let node = TreeNode {
+                    open,
+                    group: group.index,
+                    tab: None,
+                };
+                if self.query.is_empty() || group.name.to_lowercase().contains(&self.query) {
+                    rows.push(TreeRow { node, depth: 0 });
+                    if self.expanded.contains(&node) {
+                        push_tabs(rows, &self.expanded, group, open, None, 1);
+                    }
+                } else if self.search_tabs {
+                    // Show the matching tabs directly below their window:
+                    let matching_tabs = group
+                        .tabs
+                        .iter()
+                        .filter(|tab| {
+                            tab.title.to_lowercase().contains(&self.query)
+                                || tab.url.to_lowercase().contains(&self.query)
+                        })
+                        .map(|tab| TreeRow {
+                            node: TreeNode {
+                                tab: Some(tab.index),
+                                ..node
+                            },
+                            depth: 1,
+                        })
+                        .collect::<Vec<_>>();
+                    if !matching_tabs.is_empty() {
+                        rows.push(TreeRow { node, depth: 0 });
+                        rows.extend(matching_tabs);
+                    }
+                }
+            }
+        }
+    }
+
+    fn group_selection(&self, open: bool, index: u32) -> GroupSelection {
+        let (indexes, tab_indexes) = if open {
+            (
+                &self.selected_tab_groups.open_group_indexes,
+                &self.selected_tab_groups.open_tab_indexes,
+            )
+        } else {
+            (
+                &self.selected_tab_groups.closed_group_indexes,
+                &self.selected_tab_groups.closed_tab_indexes,
+            )
+        };
+        if !indexes
+            .as_ref()
+            .is_some_and(|indexes| indexes.contains(&index))
+        {
+            GroupSelection::None
+        } else if tab_indexes.contains_key(&index) {
+            GroupSelection::Partial
+        } else {
+            GroupSelection::All
+        }
+    }
+
+    fn is_tab_selected(&self, open: bool, group: u32, tab: u32) -> bool {
+        let tab_indexes = if open {
+            &self.selected_tab_groups.open_tab_indexes
+        } else {
+            &self.selected_tab_groups.closed_tab_indexes
+        };
+        match self.group_selection(open, group) {
+            GroupSelection::None => false,
+            GroupSelection::Partial => tab_indexes
+                .get(&group)
+                .is_some_and(|indexes| indexes.contains(&tab)),
+            GroupSelection::All => true,
+        }
+    }
+
+    fn change_selected_tab_group(&mut self, index: u32, open: bool, select: bool) -> bool {
+        // Selecting or deselecting a whole group discards any selection of
+        // individual tabs inside it:
+        let had_tab_selection = if open {
+            self.selected_tab_groups.open_tab_indexes.remove(&index)
+        } else {
+            self.selected_tab_groups.closed_tab_indexes.remove(&index)
+        }
+        .is_some();
+
+        let (mut indexes, mut other) = (
+            &mut self.selected_tab_groups.open_group_indexes,
+            &mut self.selected_tab_groups.closed_group_indexes,
+        );
+        if !open {
+            std::mem::swap(&mut indexes, &mut other);
+        }
+        if select {
+            let indexes = indexes.get_or_insert_with(Vec::new);
+            other.get_or_insert_with(Vec::new);
+            if !indexes.contains(&index) {
+                indexes.push(index);
+                true // regen
+            } else {
+                had_tab_selection // already selected
+            }
+        } else if let Some(indexes) = indexes {
+            let len = indexes.len();
+            indexes.retain(|v| *v != index);
+            if indexes.len() != len {
+                // Something was removed => update preview:
+                if self.selected_tab_groups.selected_groups() == 0 {
+                    // Nothing selected => select all open windows:
+                    self.selected_tab_groups.open_group_indexes = None;
+                    self.selected_tab_groups
+                        .closed_group_indexes
+                        .get_or_insert_with(Vec::new);
+                }
+                true // regen
+            } else {
+                false
+            }
+        } else {
+            false // nothing to deselect
+        }
+    }
+
+    /// Expand a window and the ancestors of a tab so that it is visible.
+    /// Returns the row that shows the window or tab.
+    fn reveal(&mut self, node: TreeNode) -> Option<IndexPath> {
+        let group = self
+            .groups(node.open)
+            .iter()
+            .find(|g| g.index == node.group)?;
+        let mut ancestors = Vec::new();
+        let mut current = node.tab;
+        while let Some(tab) = current {
+            current = group
+                .tabs
+                .iter()
+                .find(|t| t.index == tab)
+                .and_then(|tab| tab.parent)
+                .filter(|parent| !ancestors.contains(parent));
+            if let Some(parent) = current {
+                ancestors.push(parent);
+            }
+        }
+        if node.tab.is_some() {
+            self.expanded.insert(TreeNode { tab: None, ..node });
+        }
+        for tab in ancestors {
+            self.expanded.insert(TreeNode {
+                tab: Some(tab),
+                ..node
+            });
+        }
+        self.update_rows();
+
+        let section = if node.open { 0 } else { 1 };
+        let row = self.rows[section].iter().position(|row| row.node == node)?;
+        Some(IndexPath::new(row).section(section))
+    }
+
+    /// Select or deselect a tab together with all of its descendants.
+    fn change_selected_tab(&mut self, group: u32, tab: u32, open: bool, select: bool) -> bool {
+        let Some(tab_group) = self.groups(open).iter().find(|g| g.index == group) else {
+            return false;
+        };
+        let subtree = tab_group.subtree(tab);
+        let all_tabs = tab_group.tabs.len();
+
+        let mut selected = match self.group_selection(open, group) {
+            GroupSelection::None => Vec::new(),
+            GroupSelection::Partial => if open {
+                &self.selected_tab_groups.open_tab_indexes
+            } else {
+                &self.selected_tab_groups.closed_tab_indexes
+            }
+            .get(&group)
+            .cloned()
+            .unwrap_or_default(),
+            GroupSelection::All => tab_group.tabs.iter().map(|tab| tab.index).collect(),
+        };
+        let previous = selected.clone();
+        if select {
+            for tab in subtree {
+                if !selected.contains(&tab) {
+                    selected.push(tab);
+                }
+            }
+        } else {
+            selected.retain(|tab| !subtree.contains(tab));
+        }
+        if selected == previous {
+            return false;
+        }
+
+        if selected.is_empty() {
+            return self.change_selected_tab_group(group, open, false);
+        }
+        self.change_selected_tab_group(group, open, true);
+        if selected.len() != all_tabs {
+            let tab_indexes = if open {
+                &mut self.selected_tab_groups.open_tab_indexes
+            } else {
+                &mut self.selected_tab_groups.closed_tab_indexes
+            };
+            tab_indexes.insert(group, selected);
+        }
+        true
+    }
+
+    /// Indexes of the selected tabs in a window.
+    fn selected_tabs(&self, open: bool, group: &host::TabGroup) -> Vec<u32> {
+        group
+            .tabs
+            .iter()
+            .map(|tab| tab.index)
+            .filter(|&tab| self.is_tab_selected(open, group.index, tab))
+            .collect()
+    }
+
+    /// Replace the selected tabs of a window. `None` deselects the window.
+    fn set_selected_tabs(&mut self, open: bool, group: &host::TabGroup, tabs: Option<Vec<u32>>) {
+        let selected = &mut self.selected_tab_groups;
+        let (indexes, tab_indexes) = if open {
+            (
+                &mut selected.open_group_indexes,
+                &mut selected.open_tab_indexes,
+            )
+        } else {
+            (
+                &mut selected.closed_group_indexes,
+                &mut selected.closed_tab_indexes,
+            )
+        };
+        let indexes = indexes.get_or_insert_with(Vec::new);
+        indexes.retain(|&index| index != group.index);
+        tab_indexes.remove(&group.index);
+        let Some(tabs) = tabs.filter(|tabs| !tabs.is_empty() || group.tabs.is_empty()) else {
+            return;
+        };
+        indexes.push(group.index);
+        if tabs.len() != group.tabs.len() {
+            tab_indexes.insert(group.index, tabs);
+        }
+    }
+
+    /// Change the selection of many windows at once.
+    fn bulk_select(&mut self, selection: &BulkSelection) -> bool {
+        let previous = self.selected_tab_groups.clone();
+        let query = match selection {
+            BulkSelection::Matching(text) => text.to_lowercase(),
+            _ => String::new(),
+        };
+        let matches = |group: &host::TabGroup| {
+            group.name.to_lowercase().contains(&query)
+                || group.tabs.iter().any(|tab| {
+                    tab.title.to_lowercase().contains(&query)
+                        || tab.url.to_lowercase().contains(&query)
+                })
+        };
+
+        // No explicit selection of open windows means that all of them are
+        // included, so make that explicit before changing windows one at a time:
+        let all_groups = self.tab_groups.clone();
+        let selected = &mut self.selected_tab_groups;
+        if selected.open_group_indexes.is_none() {
+            selected.open_group_indexes =
+                Some(all_groups.open.iter().map(|group| group.index).collect());
+        }
+        selected.closed_group_indexes.get_or_insert_with(Vec::new);
+
+        for (open, groups) in [(true, &all_groups.open), (false, &all_groups.closed)] {
+            for group in groups {
+                let all_tabs = || Some(group.tabs.iter().map(|tab| tab.index).collect());
+                let tabs = match selection {
+                    BulkSelection::All => all_tabs(),
+                    BulkSelection::None => None,
+                    // Windows without tabs are inverted as a whole:
+                    BulkSelection::Invert if group.tabs.is_empty() => {
+                        if self.group_selection(open, group.index) == GroupSelection::None {
+                            all_tabs()
+                        } else {
+                            None
+                        }
+                    }
+                    BulkSelection::Invert => {
+                        let selected = self.selected_tabs(open, group);
+                        Some(
+                            group
+                                .tabs
+                                .iter()
+                                .map(|tab| tab.index)
+                                .filter(|tab| !selected.contains(tab))
+                                .collect(),
+                        )
+                    }
+                    BulkSelection::AllClosed if !open => all_tabs(),
+                    BulkSelection::AllClosed => continue,
+                    BulkSelection::Matching(_) if matches(group) => all_tabs(),
+                    BulkSelection::Matching(_) => None,
+                };
+                self.set_selected_tabs(open, group, tabs);
+            }
+        }
+        self.selected_tab_groups != previous
+    }
+
+    /// Check if a window or tab is selected. Windows that only have some of
+    /// their tabs selected count as selected.
+    fn is_node_selected(&self, node: TreeNode) -> bool {
+        match node.tab {
+            None => self.group_selection(node.open, node.group) != GroupSelection::None,
+            Some(tab) => self.is_tab_selected(node.open, node.group, tab),
+        }
+    }
+
+    /// Select or deselect several windows and tabs. Returns `true` if the
+    /// selection changed.
+    fn change_selected_nodes(&mut self, nodes: &[TreeNode], select: bool) -> bool {
+        let mut changed = false;
+        for node in nodes {
+            changed |= match node.tab {
+                None => self.change_selected_tab_group(node.group, node.open, select),
+                Some(tab) => self.change_selected_tab(node.group, tab, node.open, select),
+            };
+        }
+        changed
+    }
+
+    /// Selected windows and tabs in the open or closed section.
+    fn selection_counts(&self, open: bool) -> (usize, usize) {
+        self.groups(open)
+            .iter()
+            .fold((0, 0), |(windows, tabs), group| {
+                let selected_tabs = self.selected_tabs(open, group).len();
+                let selected = self.group_selection(open, group.index) != GroupSelection::None;
+                (windows + usize::from(selected), tabs + selected_tabs)
+            })
+    }
+
+    /// Summary of a window that is shown below its name.
+    fn window_details(group: &host::TabGroup) -> String {
+        let mut details = match group.tabs.len() {
+            1 => t!("count.tabs_one").to_string(),
+            count => t!("count.tabs_other", count = count).to_string(),
+        };
+        let pinned = group.tabs.iter().filter(|tab| tab.pinned).count();
+        if pinned > 0 {
+            details += &format!(", {}", t!("sidebar.pinned_count", count = pinned));
+        }
+        if let Some(closed_at) = group.closed_at.and_then(host::format_timestamp) {
+            details += &format!(", {}", t!("sidebar.closed_at", time = closed_at));
+        } else if let Some(last_accessed) = group.last_accessed().and_then(host::format_timestamp) {
+            details += &format!(", {}", t!("sidebar.used_at", time = last_accessed));
+        }
+        details
+    }
+
+    /// Card shown when hovering over a window that lists its first tabs.
+    fn window_hover_card(group: &host::TabGroup) -> impl IntoElement {
+        const MAX_TABS: usize = 8;
+
+        let last_accessed = group.last_accessed().and_then(host::format_timestamp);
+        v_flex()
+            .gap_1()
+            .max_w(px(400.))
+            .child(Label::new(group.name.clone()).font_semibold())
+            .child(Label::new(Self::window_details(group)).text_xs())
+            .when_some(last_accessed, |this, time| {
+                this.child(
+                    Label::new(t!("sidebar.last_accessed", time = time).to_string()).text_xs(),
+                )
+            })
+            .children(group.tabs.iter().take(MAX_TABS).map(|tab| {
+                Label::new(format!(
+                    "• {}{}",
+                    if tab.title.is_empty() {
+                        &tab.url
+                    } else {
+                        &tab.title
+                    },
+                    if tab.pinned {
+                        t!("sidebar.pinned")
+                    } else {
+                        "".into()
+                    },
+                ))
+                .truncate()
+            }))
+            .when(group.tabs.len() > MAX_TABS, |this| {
+                this.child(
+                    Label::new(
+                        t!("sidebar.more_tabs", count = group.tabs.len() - MAX_TABS).to_string(),
+                    )
+                    .text_xs(),
+                )
+            })
+    }
+
+    /// Command that toggles the selection of a window or a tab subtree.
+    fn toggle_node_command(&self, node: TreeNode) -> Command {
+        match node.tab {
+            None => Command::ChangeTabGroupSelection {
+                open: node.open,
+                index: node.group,
+                select: self.group_selection(node.open, node.group) != GroupSelection::All,
+            },
+            Some(tab) => Command::ChangeTabSelection {
+                open: node.open,
+                group: node.group,
+                tab,
+                select: !self.is_tab_selected(node.open, node.group, tab),
+            },
+        }
+    }
+}
+impl ListDelegate for TabGroupList {
+    type Item = ListItem;
+
+    fn sections_count(&self, _cx: &App) -> usize {
+        2 // open and closed
+    }
+
+    fn perform_search(
+        &mut self,
+        query: &str,
+        _window: &mut Window,
+        cx: &mut Context<List<Self>>,
+    ) -> gpui::Task<()> {
+        self.query = query.trim().to_lowercase();
+        // Row indexes change when filtering:
+        self.selected_item = None;
+        self.anchor_item = None;
+        self.update_rows();
+        cx.notify();
+        gpui::Task::ready(())
+    }
+
+    fn items_count(&self, section: usize, _cx: &App) -> usize {
+        self.rows.get(section).map_or(0, Vec::len)
+    }
+
+    fn render_item(
+        &self,
+        ix: IndexPath,
+        _window: &mut Window,
+        cx: &mut Context<'_, List<Self>>,
+    ) -> Option<Self::Item> {
+        let row = *self.rows.get(ix.section)?.get(ix.row)?;
+        let node = row.node;
+        let group = self
+            .groups(node.open)
+            .iter()
+            .find(|g| g.index == node.group)?;
+
+        let (text, has_children, selection) = match node.tab {
+            None => (
+                group.name.clone(),
+                !group.tabs.is_empty(),
+                self.group_selection(node.open, node.group),
+            ),
+            Some(tab_index) => {
+                let tab = group.tabs.iter().find(|tab| tab.index == tab_index)?;
+                (
+                    if tab.title.is_empty() {
+                        tab.url.clone()
+                    } else {
+                        tab.title.clone()
+                    },
+                    group.tabs.iter().any(|tab| tab.parent == Some(tab_index)),
+                    if self.is_tab_selected(node.open, node.group, tab_index) {
+                        GroupSelection::All
+                    } else {
+                        GroupSelection::None
+                    },
+                )
+            }
+        };
+
+        let expander = div().size_4().flex_none().when(has_children, |this| {
+            this.child(Icon::new(if self.expanded.contains(&node) {
+                IconName::ChevronDown
+            } else {
+                IconName::ChevronRight
+            }))
+            .on_mouse_down(
+                MouseButton::Left,
+                cx.listener(move |list, _, _window, cx| {
+                    // Don't toggle the selection of the list item:
+                    cx.stop_propagation();
+                    list.delegate_mut().toggle_expanded(node);
+                    cx.notify();
+                }),
+            )
+        });
+        let checkbox = div()
+            .size_4()
+            .flex_none()
+            .border_1()
+            .rounded_sm()
+            .border_color(cx.theme().border)
+            .children(match selection {
+                GroupSelection::None => None,
+                GroupSelection::Partial => Some(Icon::new(IconName::Minus)),
+                GroupSelection::All => Some(Icon::new(IconName::Check)),
+            });
+
+        let label = match node.tab {
+            Some(_) => Label::new(text).truncate().into_any_element(),
+            None => v_flex()
+                .id(SharedString::from(format!(
+                    "window-{}-{}",
+                    if node.open { "open" } else { "closed" },
+                    node.group
+                )))
+                .min_w_0()
+                .child(Label::new(text).truncate())
+                .child(
+                    Label::new(Self::window_details(group))
+                        .text_xs()
+                        .text_color(cx.theme().muted_foreground)
+                        .truncate(),
+                )
+                .tooltip({
+                    let group = group.clone();
+                    move |window, cx| {
+                        let group = group.clone();
+                        Tooltip::element(move |_window, _cx| Self::window_hover_card(&group))
+                            .build(window, cx)
+                    }
+                })
+                .into_any_element(),
+        };
+
+        Some(
+            ListItem::new(ix)
+                .child(
+                    h_flex()
+                        .gap_1()
+                        .pl(px(row.depth as f32 * 16.))
+                        .child(expander)
+                        .child(checkbox)
+                        .child(label),
+                )
+                .selected(node.tab.is_none() && selection != GroupSelection::None),
+        )
+    }
+
+    fn render_section_header(
+        &self,
+        section: usize,
+        _window: &mut Window,
+        _cx: &mut Context<'_, List<Self>>,
+    ) -> Option<impl IntoElement> {
+        let (title, open) = match section {
+            0 => (t!("sidebar.open_windows"), true),
+            1 => (t!("sidebar.closed_windows"), false),
+            _ => return None,
+        };
+        let total = self.groups(open).len();
+        let counts = if open && self.selected_tab_groups.open_group_indexes.is_none() {
+            // Nothing explicitly selected means all open windows are included:
+            t!("sidebar.all_count", total = total).to_string()
+        } else {
+            let (windows, tabs) = self.selection_counts(open);
+            t!(
+                "sidebar.selected_count",
+                windows = windows,
+                total = total,
+                tabs = tabs
+            )
+            .to_string()
+        };
+
+        Some(
+            h_flex()
+                .px_2()
+                .py_1()
+                .gap_2()
+                .text_sm()
+                // .text_color(cx.theme().muted_foreground)
+                .child(Icon::new(IconName::Folder))
+                .child(title.into_owned())
+                .child(counts),
+        )
+    }
+
+    fn render_section_footer(
+        &self,
+        _section: usize,
+        _window: &mut Window,
+        _cx: &mut Context<'_, List<Self>>,
+    ) -> Option<impl IntoElement> {
+        Some(div().px_2().py_1().child(""))
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: Option<IndexPath>,
+        _window: &mut Window,
+        _cx: &mut Context<List<Self>>,
+    ) {
+        self.selected_item = ix;
+    }
+
+    fn confirm(&mut self, _secondary: bool, window: &mut Window, cx: &mut Context<List<Self>>) {
+        let Some(ix) = self.selected_item else { return };
+        let Some(row) = self.rows.get(ix.section).and_then(|rows| rows.get(ix.row)) else {
+            return;
+        };
+
+        let command = match self.anchor_item {
+            // Shift-click changes all items between the previous and the
+            // current item to the same state as the previous item:
+            Some(anchor) if window.modifiers().shift && anchor.section == ix.section => {
+                let rows = &self.rows[ix.section];
+                let select = rows
+                    .get(anchor.row)
+                    .is_some_and(|anchor| self.is_node_selected(anchor.node));
+                let range = anchor.row.min(ix.row)..=anchor.row.max(ix.row);
+                Command::ChangeNodeSelection {
+                    nodes: rows
+                        .get(range)
+                        .unwrap_or_default()
+                        .iter()
+                        .map(|row| row.node)
+                        .collect(),
+                    select,
+                }
+            }
+            _ => {
+                self.anchor_item = Some(ix);
+                self.toggle_node_command(row.node)
+            }
+        };
+
+        let parent = self.parent.clone();
+        MsgSender::new(window.to_async(cx), parent)
+            .spawn(async move |_window, mut sender| {
+                sender.send(command);
+            })
+            .detach();
+
+        cx.notify();
+    }
+}
+
+/// Variants that are skipped by serde are shown in the inspector but aren't
+/// replayed. They are either results of tasks that run again when the messages
+/// that started them are replayed, or they affect things outside of the
+/// program such as files, the clipboard, the browser or the settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Command {
+    SetInputPath(String, #[serde(skip)] Option<rfd::FileHandle>),
+    #[serde(skip)]
+    BrowseInputPath,
+    LoadNewInputData,
+    /// Load the currently loaded file again.
+    ReloadInputData,
+    #[serde(skip)]
+    UpdateLoadedData(host::FileInfo),
+    #[serde(skip)]
+    ParsedTabGroups(host::AllTabGroups),
+    /// The tab groups were listed again with different tree sources.
+    #[serde(skip)]
+    UpdateTabTree(host::AllTabGroups),
+    RegeneratePreview,
+    #[serde(skip)]
+    SetPreview(String),
+    ChangeTabGroupSelection {
+        open: bool,
+        index: u32,
+        select: bool,
+    },
+    BulkSelectTabGroups(BulkSelection),
+    /// Select or deselect several windows and tabs in the sidebar, for
+    /// example with shift-click.
+    ChangeNodeSelection {
+        nodes: Vec<TreeNode>,
+        select: bool,
+    },
+    ChangeTabSelection {
+        open: bool,
+        group: u32,
+        tab: u32,
+        select: bool,
+    },
+    /// Show a tab in the sidebar.
+    RevealTab(TabLocation),
+    /// Open the selected tabs in a browser, asking first if there are many.
+    #[serde(skip)]
+    OpenSelectedTabs,
+    #[serde(skip)]
+    OpenUrls(Vec<String>),
+    SetSavePath(String),
+    #[serde(skip)]
+    BrowseSavePath,
+    /// Create missing folders when saving links.
+    SetCreateFolder(bool),
+    /// Replace existing files when saving links.
+    SetOverwrite(bool),
+    /// Show a message in the status bar and add it to the status log.
+    #[serde(skip)]
+    SetStatus(Severity, String),
+    /// Show an error in the status bar and as a notification.
+    #[serde(skip)]
+    ReportError(ErrorReport),
+    /// Links were written to a file at this path.
+    #[serde(skip)]
+    SavedLinks(PathBuf),
+    ToggleStatusLog,
+    /// Revert the latest change to the selection or output options.
+    Undo,
+    /// Apply a change that was undone again.
+    Redo,
+    ClearStatusLog,
+    /// Prompt for a file and write the status log to it.
+    #[serde(skip)]
+    ExportStatusLog,
+    #[serde(skip)]
+    CopyLinksToClipboard,
+    #[serde(skip)]
+    SaveLinksToFile,
+    GeneratePdfPreview,
+    #[serde(skip)]
+    ShowPdfPreview(Vec<host::PdfPreviewPage>),
+    SetLinkOptions(host::LinkOptions),
+    SetTableOfContents(bool),
+    SetOutputFormat(OutputFormat),
+    ToggleOutputOptions,
+    SaveTemplate {
+        previous_name: Option<String>,
+        template: template::OutputTemplate,
+    },
+    DeleteTemplate(String),
+    #[serde(skip)]
+    SetSettings(settings::Settings),
+    /// The shared settings were changed, possibly by another session tab.
+    #[serde(skip)]
+    SettingsChanged,
+    /// The user interface language was changed.
+    #[serde(skip)]
+    LanguageChanged,
+    /// Write the shared settings to the settings file.
+    #[serde(skip)]
+    SaveSettings,
+    #[serde(skip)]
+    ToggleInspector,
+    /// Start or stop recording messages for the inspector.
+    #[serde(skip)]
+    ToggleRecording,
+    #[serde(skip)]
+    ClearRecording,
+    /// Prompt for a file and write the recorded messages to it.
+    #[serde(skip)]
+    SaveRecording,
+    /// Prompt for a recording and replay it.
+    #[serde(skip)]
+    OpenRecording,
+    #[serde(skip)]
+    ReplayRecording(Vec<TraceEntry>),
+    /// The loaded file was changed by another program.
+    InputFileChanged,
+    /// Load the loaded file again after it changed, keeping the selection.
+    ReloadChangedFile,
+    #[serde(skip)]
+    ReloadedTabGroups(host::AllTabGroups),
+}
+impl Message for Command {
+    fn describe(&self) -> String {
+        match self {
+            // Loaded data and previews can be very large:
+            Command::UpdateLoadedData(_) => "UpdateLoadedData".to_owned(),
+            Command::ParsedTabGroups(groups) => format!(
+                "ParsedTabGroups({} open windows, {} closed windows)",
+                groups.open.len(),
+                groups.closed.len()
+            ),
+            Command::ReloadedTabGroups(groups) => format!(
+                "ReloadedTabGroups({} open windows, {} closed windows)",
+                groups.open.len(),
+                groups.closed.len()
+            ),
+            Command::UpdateTabTree(_) => "UpdateTabTree".to_owned(),
+            Command::SetPreview(preview) => format!("SetPreview({} bytes)", preview.len()),
+            Command::OpenUrls(urls) => format!("OpenUrls({} urls)", urls.len()),
+            Command::ReportError(report) => format!("ReportError({:?})", report.error),
+            Command::ShowPdfPreview(pages) => format!("ShowPdfPreview({} pages)", pages.len()),
+            Command::SetSettings(_) => "SetSettings".to_owned(),
+            Command::ReplayRecording(entries) => {
+                format!("ReplayRecording({} entries)", entries.len())
+            }
+            _ => format!("{self:?}"),
+        }
+    }
+
+    fn to_json(&self) -> Option<serde_json::Value> {
+        serde_json::to_value(self).ok()
+    }
+}
+/// Loads the selected input file.
+const LOAD_TASK: TaskId = TaskId("load");
+/// Lists the tab groups again after the tree sources changed.
+const TAB_TREE_TASK: TaskId = TaskId("tab-tree");
+/// Generates the preview text. Restarted whenever the selection changes so that
+/// an outdated preview never replaces a newer one.
+const PREVIEW_TASK: TaskId = TaskId("preview");
+const PDF_PREVIEW_TASK: TaskId = TaskId("pdf-preview");
+/// Watches the loaded file for changes.
+const LIVE_RELOAD_TASK: TaskId = TaskId("live-reload");
+/// Waits for the loaded file to stop changing and then loads it again.
+const RELOAD_TASK: TaskId = TaskId("reload");
+/// Firefox writes session files in several steps, so wait until the file
+/// hasn't changed for this long before reloading it.
+const RELOAD_DELAY: Duration = Duration::from_secs(1);
+/// Wait this long after the selection changed before regenerating the preview,
+/// so that quickly clicking several checkboxes only generates it once.
+const PREVIEW_DELAY: Duration = Duration::from_millis(150);
+const SAVE_SETTINGS_TASK: TaskId = TaskId("save-settings");
+/// Wait this long after the settings changed before writing them to the
+/// settings file, so that typing into an input doesn't write it every time.
+const SAVE_SETTINGS_DELAY: Duration = Duration::from_millis(500);
+
+impl Update<Command> for FirefoxSessionUtility {
+    fn update(&mut self, cx: &mut Context<Self>, msg: Command) -> Effect<Command> {
+        let records_undo = !matches!(msg, Command::Undo | Command::Redo);
+        let effect = self.handle_command(cx, msg);
+        if records_undo {
+            self.record_undo_state(cx);
+        }
+        effect
+    }
+
+    fn summary(&self, cx: &App) -> String {
+        let list = self.tab_group_list.read(cx).delegate();
+        let selected_tabs = list
+            .tab_groups
+            .selected_groups(&list.selected_tab_groups)
+            .map(|group| group.tabs.len())
+            .sum::<usize>();
+        format!(
+            "input: {:?}, open windows: {}, selected tabs: {}, format: {}, preview: {} bytes",
+            self.loaded_input.read(cx).value().as_str(),
+            list.tab_groups.open.len(),
+            selected_tabs,
+            self.selected_format
+                .as_ref()
+                .map(OutputFormat::id)
+                .unwrap_or_default(),
+            self.preview.read(cx).value().len(),
+        )
+    }
+}
+impl FirefoxSessionUtility {
+    fn handle_command(&mut self, cx: &mut Context<Self>, msg: Command) -> Effect<Command> {
+        match msg {
+            Command::SetInputPath(input_path, data) => {
+                self.new_input_data = data;
+                return set_input_value(&self.new_input, input_path);
+            }
+            Command::BrowseInputPath => return Self::browse_input_path(),
+            Command::ReloadInputData => {
+                let Some(data) = &self.loaded_input_data else {
+                    self.set_status(cx, Severity::Warning, t!("status.no_data").to_string());
+                    return Effect::none();
+                };
+                let input_path = self.loaded_input.read(cx).value().to_string();
+                let file_handle = data.file_handle.clone();
+                return Effect::batch([
+                    Effect::send(Command::SetInputPath(input_path, file_handle)),
+                    Effect::send(Command::LoadNewInputData),
+                ]);
+            }
+            Command::LoadNewInputData => {
+                let input_path = self.new_input.read(cx).value();
+                let show_input_path = set_input_value(&self.loaded_input, input_path.clone());
+                let save_settings = self.update_settings(cx, |settings| {
+                    settings.last_input_path = Some(input_path.to_string());
+                });
+
+                let path = if let Some(data) = &self.new_input_data {
+                    data.path().to_owned()
+                } else {
+                    PathBuf::from(input_path.as_str())
+                };
+                let mut data = host::FileInfo::new(path);
+                data.file_handle = self.new_input_data.clone();
+                self.loaded_input_data = Some(data.clone());
+
+                self.tab_group_list.update(cx, |tab_group_list, _cx| {
+                    let selected = &mut tab_group_list.delegate_mut().selected_tab_groups;
+                    selected.open_group_indexes = None;
+                    selected.closed_group_indexes = Some(Vec::new());
+                    selected.open_tab_indexes.clear();
+                    selected.closed_tab_indexes.clear();
+                });
+                self.set_status(cx, Severity::Info, t!("status.reading_input").to_string());
+
+                // The preview of the previous file and a pending reload of it
+                // are no longer needed:
+                let cancel =
+                    Effect::batch([Effect::cancel(PREVIEW_TASK), Effect::cancel(RELOAD_TASK)]);
+                let tree_sources = self.link_options.tree_sources.clone();
+                let load = Self::load_tab_groups(data, tree_sources, Command::ParsedTabGroups)
+                    .with_id(LOAD_TASK);
+                return Effect::batch([show_input_path, cancel, load, save_settings]);
             }
             Command::UpdateLoadedData(data) => {
                 self.loaded_input_data = Some(data);
             }
             Command::ParsedTabGroups(all_groups) => {
+                let input_path = self.loaded_input.read(cx).value().to_string();
+                let window_count = all_groups.open.len();
+                let save_settings = self.update_settings(cx, |settings| {
+                    settings.add_recent_file(input_path, window_count);
+                });
+
                 self.tab_group_list.update(cx, |tab_group_list, _cx| {
-                    tab_group_list.delegate_mut().tab_groups = all_groups;
+                    tab_group_list.delegate_mut().set_tab_groups(all_groups);
+                });
+                // Selections from another file don't make sense for this one:
+                self.history.clear();
+                self.undo_baseline = Some(self.undo_state(cx));
+                return Effect::batch([
+                    Effect::send(Command::RegeneratePreview),
+                    self.update_subscriptions(cx),
+                    save_settings,
+                ]);
+            }
+            Command::UpdateTabTree(all_groups) => {
+                self.tab_group_list.update(cx, |tab_group_list, cx| {
+                    tab_group_list.delegate_mut().update_tab_tree(all_groups);
+                    cx.notify();
                 });
-                self.update(window, cx, Command::RegeneratePreview);
             }
             Command::RegeneratePreview => {
                 let Some(data) = self.loaded_input_data.clone() else {
-                    return;
+                    return Effect::none();
                 };
                 let options = self
                     .tab_group_list
@@ -469,98 +2260,883 @@ impl Update<Command> for FirefoxSessionUtility {
                     .selected_tab_groups
                     .clone();
 
-                self.set_status(window, cx, "Generating preview");
-                MsgSender::from_cx(window, cx)
-                    .spawn(async move |_window, mut sender| {
-                        let cmd = match data.to_text_links(options).await {
-                            Ok(preview) => Command::SetPreview(preview),
-                            Err(e) => {
-                                Command::SetStatus(format!("Failed to generate preview: {e}"))
-                            }
-                        };
-                        sender.send(cmd);
-                    })
-                    .detach();
+                let link_options = self.link_options.clone();
+
+                let output_format = self.selected_format.clone();
+                if let Some((preview, _)) = output_format
+                    .and_then(|output_format| self.render_text_output(&output_format, cx))
+                {
+                    return Effect::batch([
+                        Effect::cancel(PREVIEW_TASK),
+                        Effect::send(Command::SetPreview(preview)),
+                    ]);
+                }
+
+                self.set_status(
+                    cx,
+                    Severity::Info,
+                    t!("status.generating_preview").to_string(),
+                );
+                return Effect::perform(async move {
+                    match data.to_text_links(options, link_options).await {
+                        Ok(preview) => Command::SetPreview(preview),
+                        Err(e) => Command::SetStatus(
+                            Severity::Error,
+                            t!("status.preview_failed", error = e).to_string(),
+                        ),
+                    }
+                })
+                .with_id(PREVIEW_TASK);
+            }
+            Command::SetPreview(v) => {
+                self.set_status(cx, Severity::Success, t!("status.loaded").to_string());
+                return set_input_value(&self.preview, v);
+            }
+            Command::ChangeTabGroupSelection {
+                open,
+                index,
+                select,
+            } => {
+                let changed = self.tab_group_list.update(cx, |tab_group_list, cx| {
+                    cx.notify();
+                    tab_group_list
+                        .delegate_mut()
+                        .change_selected_tab_group(index, open, select)
+                });
+                if changed {
+                    return Effect::send_later(PREVIEW_DELAY, Command::RegeneratePreview)
+                        .with_id(PREVIEW_TASK);
+                }
+            }
+            Command::BulkSelectTabGroups(selection) => {
+                let changed = self.tab_group_list.update(cx, |tab_group_list, cx| {
+                    cx.notify();
+                    tab_group_list.delegate_mut().bulk_select(&selection)
+                });
+                if changed {
+                    return Effect::send_later(PREVIEW_DELAY, Command::RegeneratePreview)
+                        .with_id(PREVIEW_TASK);
+                }
+            }
+            Command::ChangeNodeSelection { nodes, select } => {
+                let changed = self.tab_group_list.update(cx, |tab_group_list, cx| {
+                    cx.notify();
+                    tab_group_list
+                        .delegate_mut()
+                        .change_selected_nodes(&nodes, select)
+                });
+                if changed {
+                    return Effect::send_later(PREVIEW_DELAY, Command::RegeneratePreview)
+                        .with_id(PREVIEW_TASK);
+                }
+            }
+            Command::ChangeTabSelection {
+                open,
+                group,
+                tab,
+                select,
+            } => {
+                let changed = self.tab_group_list.update(cx, |tab_group_list, cx| {
+                    cx.notify();
+                    tab_group_list
+                        .delegate_mut()
+                        .change_selected_tab(group, tab, open, select)
+                });
+                if changed {
+                    return Effect::send_later(PREVIEW_DELAY, Command::RegeneratePreview)
+                        .with_id(PREVIEW_TASK);
+                }
+            }
+            Command::RevealTab(location) => {
+                let TabLocation::Window(node) = location else {
+                    self.set_status(
+                        cx,
+                        Severity::Warning,
+                        t!("status.closed_tab_not_in_sidebar").to_string(),
+                    );
+                    return Effect::none();
+                };
+                let revealed = self.tab_group_list.update(cx, |list, cx| {
+                    cx.notify();
+                    list.delegate_mut().reveal(node)
+                });
+                if let Some(ix) = revealed {
+                    let list = self.tab_group_list.clone();
+                    return Effect::window(move |window, cx| {
+                        list.update(cx, |list, cx| {
+                            list.set_selected_index(Some(ix), window, cx);
+                            list.scroll_to_item(ix, window, cx);
+                        });
+                    });
+                }
+            }
+            Command::OpenSelectedTabs => {
+                let list = self.tab_group_list.read(cx).delegate();
+                let urls = list
+                    .tab_groups
+                    .selected_groups(&list.selected_tab_groups)
+                    .flat_map(|group| group.tabs)
+                    .map(|tab| tab.url)
+                    .collect::<Vec<_>>();
+                if urls.is_empty() {
+                    self.set_status(
+                        cx,
+                        Severity::Warning,
+                        t!("status.no_tabs_selected").to_string(),
+                    );
+                    return Effect::none();
+                }
+                if urls.len() <= settings::Settings::global(cx).open_tabs_confirm_limit {
+                    return Effect::send(Command::OpenUrls(urls));
+                }
+                let view = cx.weak_entity();
+                return Effect::window(move |window, cx| {
+                    window.open_modal(cx, move |modal, _window, _cx| {
+                        let urls = urls.clone();
+                        let view = view.clone();
+                        modal
+                            .confirm()
+                            .title(t!("open_tabs.title").to_string())
+                            .child(t!("open_tabs.confirm", count = urls.len()).to_string())
+                            .on_ok(move |_, window, cx| {
+                                let urls = urls.clone();
+                                _ = view.update(cx, |view, cx| {
+                                    view.dispatch(window, cx, Command::OpenUrls(urls));
+                                });
+                                true
+                            })
+                    });
+                });
+            }
+            Command::OpenUrls(urls) => {
+                let command = settings::Settings::global(cx)
+                    .browser_command
+                    .trim()
+                    .to_owned();
+                if command.is_empty() {
+                    for url in &urls {
+                        cx.open_url(url);
+                    }
+                } else if let Err(e) = host::open_urls_with_command(&command, &urls) {
+                    self.set_status(
+                        cx,
+                        Severity::Error,
+                        t!("status.open_tabs_failed", error = e).to_string(),
+                    );
+                    return Effect::none();
+                }
+                self.set_status(
+                    cx,
+                    Severity::Success,
+                    t!("status.opened_tabs", count = urls.len()).to_string(),
+                );
+            }
+            Command::BrowseSavePath => return Self::browse_save_path(),
+            Command::CopyLinksToClipboard => {
+                cx.write_to_clipboard(ClipboardItem::new_string(
+                    self.preview.read(cx).value().as_str().to_owned(),
+                ));
+                self.set_status(cx, Severity::Success, t!("status.copied").to_string());
+            }
+            Command::SetSavePath(v) => {
+                return Effect::batch([
+                    set_input_value(&self.output_path, v.clone()),
+                    self.update_settings(cx, |settings| settings.output_path = v),
+                ]);
+            }
+            Command::SetCreateFolder(create_folder) => {
+                self.create_folder = create_folder;
+                cx.notify();
+                return self.save_settings(cx);
+            }
+            Command::SetOverwrite(overwrite) => {
+                self.overwrite = overwrite;
+                cx.notify();
+                return self.save_settings(cx);
+            }
+            Command::SetStatus(severity, message) => {
+                self.set_status(cx, severity, message);
+            }
+            Command::ReportError(report) => {
+                self.set_status(cx, Severity::Error, report.summary());
+                let view = cx.weak_entity();
+                return Effect::window(move |window, cx| {
+                    window.push_notification(
+                        Notification::new()
+                            .with_type(NotificationType::Error)
+                            .title(report.title())
+                            .message(report.summary())
+                            .autohide(false)
+                            .action(move |_window, _cx| {
+                                let view = view.clone();
+                                let report = report.clone();
+                                Button::new("error-details")
+                                    .label(t!("error_report.details").to_string())
+                                    .small()
+                                    .on_click(move |_, window, cx| {
+                                        ErrorDetails::open_modal(
+                                            window,
+                                            cx,
+                                            view.clone(),
+                                            report.clone(),
+                                        );
+                                    })
+                            }),
+                        cx,
+                    );
+                });
+            }
+            Command::SavedLinks(path) => {
+                self.set_status(cx, Severity::Success, t!("status.saved").to_string());
+                return Effect::window(move |window, cx| {
+                    window.push_notification(
+                        Notification::new()
+                            .with_type(NotificationType::Success)
+                            .message(t!("status.saved").to_string())
+                            .action(move |_window, _cx| {
+                                let path = path.clone();
+                                Button::new("reveal-saved-links")
+                                    .label(t!("notification.reveal").to_string())
+                                    .small()
+                                    .on_click(move |_, _window, cx| cx.reveal_path(&path))
+                            }),
+                        cx,
+                    );
+                });
+            }
+            Command::Undo | Command::Redo => {
+                let current = self.undo_state(cx);
+                let state = if matches!(msg, Command::Undo) {
+                    self.history.undo(current)
+                } else {
+                    self.history.redo(current)
+                };
+                let Some(state) = state else {
+                    self.set_status(
+                        cx,
+                        Severity::Warning,
+                        t!("status.nothing_to_undo").to_string(),
+                    );
+                    return Effect::none();
+                };
+                return Effect::batch([
+                    self.restore_undo_state(cx, state),
+                    Effect::send(Command::RegeneratePreview),
+                ]);
+            }
+            Command::ToggleStatusLog => {
+                self.show_status_log = !self.show_status_log;
+                cx.notify();
+            }
+            Command::ClearStatusLog => {
+                self.status_log.clear();
+                cx.notify();
+            }
+            Command::ToggleInspector => {
+                self.show_inspector = !self.show_inspector;
+                cx.notify();
+            }
+            Command::ToggleRecording => {
+                if trace::is_recording(cx) {
+                    trace::stop_recording(cx);
+                } else {
+                    trace::start_recording(cx);
+                }
+                cx.notify();
+            }
+            Command::ClearRecording => {
+                let id = cx.entity_id();
+                trace::clear(cx, id);
+                cx.notify();
+            }
+            Command::SaveRecording => {
+                let text = trace::to_json_lines(trace::entries(cx, cx.entity_id()));
+                return Effect::with_window(move |window, _cx| {
+                    let prompt = host::prompt_save_file(Some(&host::NoDisplayHandle(&*window)));
+                    Effect::spawn(|mut sender| async move {
+                        let Some(file) = prompt.await else {
+                            return;
+                        };
+                        let path = file.path().to_owned();
+                        sender.send(
+                            match host::save_text_file(path, text, "jsonl".to_owned(), true, true)
+                                .await
+                            {
+                                Ok(_) => Command::SetStatus(
+                                    Severity::Success,
+                                    t!("status.saved_recording").to_string(),
+                                ),
+                                Err(e) => Command::SetStatus(
+                                    Severity::Error,
+                                    t!("status.save_recording_failed", error = e).to_string(),
+                                ),
+                            },
+                        );
+                    })
+                });
+            }
+            Command::OpenRecording => {
+                return Effect::with_window(move |window, _cx| {
+                    let prompt =
+                        host::prompt_load_recording(Some(&host::NoDisplayHandle(&*window)));
+                    Effect::spawn(|mut sender| async move {
+                        let Some(file) = prompt.await else {
+                            return;
+                        };
+                        let text = String::from_utf8_lossy(&file.read().await).into_owned();
+                        sender.send(match trace::from_json_lines(&text) {
+                            Ok(entries) => Command::ReplayRecording(entries),
+                            Err(e) => Command::SetStatus(
+                                Severity::Error,
+                                t!("status.replay_failed", error = e).to_string(),
+                            ),
+                        });
+                    })
+                });
+            }
+            Command::ReplayRecording(entries) => match trace::replay(&entries) {
+                Ok(effect) => {
+                    self.set_status(cx, Severity::Info, t!("status.replaying").to_string());
+                    return effect;
+                }
+                Err(e) => {
+                    self.set_status(
+                        cx,
+                        Severity::Error,
+                        t!("status.replay_failed", error = e).to_string(),
+                    );
+                }
+            },
+            Command::ExportStatusLog => {
+                let text = self.status_log.export();
+                return Effect::with_window(move |window, _cx| {
+                    let prompt = host::prompt_save_file(Some(&host::NoDisplayHandle(&*window)));
+                    Effect::spawn(|mut sender| async move {
+                        let Some(file) = prompt.await else {
+                            return;
+                        };
+                        let path = file.path().to_owned();
+                        sender.send(
+                            match host::save_text_file(path, text, "log".to_owned(), true, true)
+                                .await
+                            {
+                                Ok(_) => Command::SetStatus(
+                                    Severity::Success,
+                                    t!("status.exported_log").to_string(),
+                                ),
+                                Err(e) => Command::SetStatus(
+                                    Severity::Error,
+                                    t!("status.export_log_failed", error = e).to_string(),
+                                ),
+                            },
+                        );
+                    })
+                });
+            }
+            Command::SaveLinksToFile => {
+                let save_settings = self.save_settings(cx);
+                let save_path = PathBuf::from(self.output_path.read(cx).value().as_str());
+                return Effect::batch([save_settings, self.save_links(cx, save_path)]);
+            }
+            Command::InputFileChanged => {
+                return Effect::send_later(RELOAD_DELAY, Command::ReloadChangedFile)
+                    .with_id(RELOAD_TASK);
+            }
+            Command::ReloadChangedFile => {
+                let Some(loaded) = &self.loaded_input_data else {
+                    return Effect::none();
+                };
+                // Unlike a manual reload this leaves the input path alone, and
+                // the selection is kept once the file has been parsed:
+                let mut data = host::FileInfo::new(loaded.file_path.to_path_buf());
+                data.file_handle = loaded.file_handle.clone();
+                let tree_sources = self.link_options.tree_sources.clone();
+                return Self::load_tab_groups(data, tree_sources, Command::ReloadedTabGroups)
+                    .with_id(RELOAD_TASK);
+            }
+            Command::ReloadedTabGroups(all_groups) => {
+                let changes = self.tab_group_list.update(cx, |tab_group_list, cx| {
+                    let changes = tab_group_list.delegate_mut().reload_tab_groups(all_groups);
+                    cx.notify();
+                    changes
+                });
+                // Keep undo working for the windows and tabs that still exist:
+                self.history
+                    .update_all(|state| state.selection = changes.apply(&state.selection));
+                if let Some(baseline) = &mut self.undo_baseline {
+                    baseline.selection = changes.apply(&baseline.selection);
+                }
+                self.set_status(cx, Severity::Success, t!("status.reloaded").to_string());
+                return Effect::send(Command::RegeneratePreview);
+            }
+            Command::GeneratePdfPreview => {
+                let Some(data) = self.loaded_input_data.clone() else {
+                    return Effect::none();
+                };
+                let selected = self
+                    .tab_group_list
+                    .read(cx)
+                    .delegate()
+                    .selected_tab_groups
+                    .clone();
+                let Some(output_format) = self
+                    .selected_format
+                    .as_ref()
+                    .and_then(OutputFormat::as_builtin)
+                else {
+                    return Effect::none();
+                };
+                let link_options = self.link_options.clone();
+
+                self.set_status(
+                    cx,
+                    Severity::Info,
+                    t!("status.generating_pdf_preview").to_string(),
+                );
+                return Effect::perform(async move {
+                    match data
+                        .render_pdf_preview(selected, link_options, output_format)
+                        .await
+                    {
+                        Ok(pages) => Command::ShowPdfPreview(pages),
+                        Err(e) => Command::SetStatus(
+                            Severity::Error,
+                            t!("status.pdf_preview_failed", error = e).to_string(),
+                        ),
+                    }
+                })
+                .with_id(PDF_PREVIEW_TASK);
+            }
+            Command::ShowPdfPreview(pages) => {
+                self.set_status(
+                    cx,
+                    Severity::Success,
+                    t!("status.pdf_preview_pages", count = pages.len()).to_string(),
+                );
+                let pages = pages
+                    .into_iter()
+                    .map(|page| {
+                        let image = Image::from_bytes(ImageFormat::Png, page.png.to_vec());
+                        (Arc::new(image), page.width, page.height)
+                    })
+                    .collect::<Vec<_>>();
+                return Effect::window(move |window, cx| {
+                    window.open_modal(cx, move |modal, _window, _cx| {
+                        modal
+                            .title(t!("pdf_preview.title").to_string())
+                            .width(px(800.))
+                            .child(
+                                v_flex()
+                                    .id("pdf-preview-pages")
+                                    .h(px(600.))
+                                    .overflow_y_scroll()
+                                    .items_center()
+                                    .gap_4()
+                                    .children(pages.iter().map(|(image, width, height)| {
+                                        img(image.clone()).w(px(*width)).h(px(*height)).border_1()
+                                    })),
+                            )
+                    });
+                });
+            }
+            Command::SetLinkOptions(link_options) => {
+                if self.link_options == link_options {
+                    return Effect::none();
+                }
+                let tree_sources_changed =
+                    self.link_options.tree_sources != link_options.tree_sources;
+                self.link_options = link_options;
+                cx.notify();
+
+                let regenerate = Effect::send(Command::RegeneratePreview);
+                let Some(data) = self
+                    .loaded_input_data
+                    .clone()
+                    .filter(|_| tree_sources_changed)
+                else {
+                    return regenerate;
+                };
+                // The sidebar should show the same tree as the generated links:
+                let tree_sources = self.link_options.tree_sources.clone();
+                let update_tree = Effect::perform(async move {
+                    match data.get_groups_from_session(true, &tree_sources).await {
+                        Ok(all_groups) => Command::UpdateTabTree(all_groups),
+                        Err(e) => Command::ReportError(ErrorReport::new(
+                            FailedTask::ListWindows,
+                            data.file_path.to_path_buf(),
+                            e,
+                        )),
+                    }
+                })
+                .with_id(TAB_TREE_TASK);
+                return Effect::batch([regenerate, update_tree]);
+            }
+            Command::SaveTemplate {
+                previous_name,
+                template,
+            } => {
+                let template = Arc::new(template);
+                self.templates.retain(|existing| {
+                    Some(&existing.name) != previous_name.as_ref() && existing.name != template.name
+                });
+                self.templates.push(template.clone());
+                return Effect::batch([
+                    self.update_output_formats(cx, Some(OutputFormat::Template(template))),
+                    self.save_settings(cx),
+                    Effect::send(Command::RegeneratePreview),
+                ]);
+            }
+            Command::DeleteTemplate(name) => {
+                self.templates.retain(|existing| existing.name != name);
+                return Effect::batch([
+                    self.update_output_formats(
+                        cx,
+                        Some(OutputFormat::Builtin(host::FormatInfo::PDF)),
+                    ),
+                    self.save_settings(cx),
+                    Effect::send(Command::RegeneratePreview),
+                ]);
+            }
+            Command::SetSettings(settings) => {
+                let previous = settings::Settings::global(cx).clone();
+                if previous == settings {
+                    return Effect::none();
+                }
+                self.create_folder = settings.create_folder;
+                self.overwrite = settings.overwrite;
+                let mut effects = Vec::new();
+                if self.output_path.read(cx).value().as_str() != settings.output_path {
+                    effects.push(set_input_value(
+                        &self.output_path,
+                        settings.output_path.clone(),
+                    ));
+                }
+                if (previous.theme, previous.font_size) != (settings.theme, settings.font_size) {
+                    let settings = settings.clone();
+                    effects.push(Effect::window(move |window, cx| {
+                        apply_appearance(&settings, Some(window), cx);
+                    }));
+                }
+                if previous.language != settings.language {
+                    let language = settings.language.clone();
+                    effects.push(Effect::with_window(move |_window, cx| {
+                        locale::set_language(language.as_deref());
+                        cx.refresh_windows();
+                        Effect::send(Command::LanguageChanged)
+                    }));
+                }
+                cx.notify();
+                effects.push(self.update_settings(cx, |current| *current = settings));
+                return Effect::batch(effects);
+            }
+            Command::LanguageChanged => return self.update_translations(cx),
+            Command::SaveSettings => {
+                if let Err(e) = settings::Settings::global(cx).save() {
+                    self.set_status(
+                        cx,
+                        Severity::Error,
+                        t!("status.save_settings_failed", error = e).to_string(),
+                    );
+                }
+            }
+            Command::SettingsChanged => {
+                let settings = settings::Settings::global(cx).clone();
+                let mut effects = Vec::new();
+                let mut set_text = |input: &Entity<InputState>, text: String| {
+                    // Don't move the cursor of an input that already shows the text:
+                    if input.read(cx).value().as_str() != text {
+                        effects.push(set_input_value(input, text));
+                    }
+                };
+                set_text(&self.browser_command, settings.browser_command.clone());
+                set_text(&self.script_profile, settings.script_profile.clone());
+                // Keep the text that is being typed if it's the same number:
+                if self
+                    .open_tabs_confirm_limit
+                    .read(cx)
+                    .value()
+                    .trim()
+                    .parse::<usize>()
+                    .ok()
+                    != Some(settings.open_tabs_confirm_limit)
+                {
+                    set_text(
+                        &self.open_tabs_confirm_limit,
+                        settings.open_tabs_confirm_limit.to_string(),
+                    );
+                }
+                effects.push(self.update_recent_files(cx));
+
+                let templates = settings
+                    .templates
+                    .iter()
+                    .cloned()
+                    .map(Arc::new)
+                    .collect::<Vec<_>>();
+                if templates != self.templates {
+                    self.templates = templates;
+                    effects.push(self.update_output_formats(cx, None));
+                    // Templates are shared by all session tabs, so changes to
+                    // them made elsewhere aren't undone here:
+                    if let Some(baseline) = &mut self.undo_baseline {
+                        baseline.templates = self.templates.clone();
+                    }
+                }
+                cx.notify();
+                effects.push(self.update_subscriptions(cx));
+                return Effect::batch(effects);
             }
-            Command::SetPreview(v) => {
-                self.preview.update(cx, |preview, cx| {
-                    preview.set_value(v, window, cx);
-                });
-                self.set_status(window, cx, "Successfully loaded session data");
+            Command::SetOutputFormat(format) => {
+                // The dropdown reports the format it already shows after it
+                // was changed here:
+                if self.selected_format.as_ref() == Some(&format) {
+                    return Effect::none();
+                }
+                self.selected_format = Some(format);
+                cx.notify();
+                return Effect::batch([
+                    self.show_output_format(None),
+                    self.save_settings(cx),
+                    Effect::send(Command::RegeneratePreview),
+                ]);
             }
-            Command::ChangeTabGroupSelection { .. } => {
-                // TODO: update sidebar list
+            Command::ToggleOutputOptions => {
+                self.show_output_options = !self.show_output_options;
+                cx.notify();
             }
-            Command::SetSavePath(v) => {
-                self.output_path.update(cx, |output_path, cx| {
-                    output_path.set_value(v, window, cx);
+            Command::SetTableOfContents(table_of_content) => {
+                self.tab_group_list.update(cx, |tab_group_list, cx| {
+                    tab_group_list
+                        .delegate_mut()
+                        .selected_tab_groups
+                        .table_of_content = table_of_content;
+                    cx.notify();
                 });
+                return Effect::send(Command::RegeneratePreview);
             }
-            Command::SetStatus(v) => {
-                self.set_status(window, cx, v);
+        }
+        Effect::none()
+    }
+
+    /// Read, decompress and parse a session file and then send `parsed` with
+    /// its tab groups.
+    fn load_tab_groups(
+        mut data: host::FileInfo,
+        tree_sources: Vec<host::TreeSource>,
+        parsed: fn(host::AllTabGroups) -> Command,
+    ) -> Effect<Command> {
+        let path = data.file_path.to_path_buf();
+        Effect::spawn(|mut sender| async move {
+            let report =
+                |task, error| Command::ReportError(ErrorReport::new(task, path.clone(), error));
+            if let Err(e) = data.load_data().await {
+                sender.send(report(FailedTask::ReadFile, e));
+                return;
+            };
+            sender.send(Command::UpdateLoadedData(data.clone()));
+            loop {
+                match &data.data {
+                    Some(host::FileData::Compressed { .. }) => {
+                        sender.send(Command::SetStatus(
+                            Severity::Info,
+                            t!("status.decompressing").to_string(),
+                        ));
+                        if let Err(e) = data.decompress_data().await {
+                            sender.send(report(FailedTask::Decompress, e));
+                            return;
+                        }
+                    }
+                    Some(host::FileData::Uncompressed { .. }) => {
+                        sender.send(Command::SetStatus(
+                            Severity::Info,
+                            t!("status.parsing").to_string(),
+                        ));
+                        if let Err(e) = data.parse_session_data().await {
+                            sender.send(report(FailedTask::Parse, e));
+                            return;
+                        }
+                    }
+                    Some(host::FileData::Parsed { .. }) => {
+                        sender.send(
+                            match data.get_groups_from_session(true, &tree_sources).await {
+                                Ok(all_groups) => parsed(all_groups),
+                                Err(e) => report(FailedTask::ListWindows, e),
+                            },
+                        );
+                        return;
+                    }
+                    None => unreachable!("we just loaded the data"),
+                }
+                sender.send(Command::UpdateLoadedData(data.clone()));
             }
-            Command::SaveLinksToFile => {
-                let Some(data) = self.loaded_input_data.clone() else {
-                    return;
-                };
-                let save_path = PathBuf::from(self.output_path.read(cx).value().as_str());
-                let selected = self
-                    .tab_group_list
-                    .read(cx)
-                    .delegate()
-                    .selected_tab_groups
-                    .clone();
-                let Some(output_format) = self.output_format.read(cx).selected_value() else {
-                    return;
-                };
-                let output_options = host::OutputOptions {
-                    format: *output_format,
-                    overwrite: self.overwrite,
-                    create_folder: self.create_folder,
-                };
+        })
+    }
 
-                self.set_status(window, cx, "Saving links to file");
+    /// Write the selected links to a file.
+    fn save_links(&mut self, cx: &mut Context<Self>, save_path: PathBuf) -> Effect<Command> {
+        let Some(data) = self.loaded_input_data.clone() else {
+            return Effect::none();
+        };
+        let selected = self
+            .tab_group_list
+            .read(cx)
+            .delegate()
+            .selected_tab_groups
+            .clone();
+        let Some(output_format) = self.selected_format.clone() else {
+            return Effect::none();
+        };
+        if let Some((text, file_ext)) = self.render_text_output(&output_format, cx) {
+            let (overwrite, create_folder) = (self.overwrite, self.create_folder);
 
-                let view = cx.weak_entity();
-                window
-                    .spawn(cx, async move |window| {
-                        let new_status = if let Err(e) =
-                            data.save_links(save_path, selected, output_options).await
-                        {
-                            Command::SetStatus(format!("Failed to save links to file: {e}"))
-                        } else {
-                            Command::SetStatus("Successfully saved links to a file".to_owned())
-                        };
-                        MsgSender::new(window.clone(), view).send(new_status);
-                    })
-                    .detach();
+            self.set_status(cx, Severity::Info, t!("status.saving").to_string());
+            return Effect::perform(async move {
+                match host::save_text_file(
+                    save_path.clone(),
+                    text,
+                    file_ext,
+                    overwrite,
+                    create_folder,
+                )
+                .await
+                {
+                    Ok(path) => Command::SavedLinks(path),
+                    Err(e) => {
+                        Command::ReportError(ErrorReport::new(FailedTask::SaveLinks, save_path, e))
+                    }
+                }
+            });
+        }
+        let Some(format) = output_format.as_builtin() else {
+            return Effect::none();
+        };
+        let output_options = host::OutputOptions {
+            format,
+            overwrite: self.overwrite,
+            create_folder: self.create_folder,
+        };
+        let link_options = self.link_options.clone();
+
+        self.set_status(cx, Severity::Info, t!("status.saving").to_string());
+
+        Effect::perform(async move {
+            match data
+                .save_links(save_path.clone(), selected, output_options, link_options)
+                .await
+            {
+                Ok(path) => Command::SavedLinks(path),
+                Err(e) => {
+                    Command::ReportError(ErrorReport::new(FailedTask::SaveLinks, save_path, e))
+                }
             }
+        })
+    }
+
+    /// Start or stop subscriptions after the loaded file or the settings
+    /// changed.
+    fn update_subscriptions(&mut self, cx: &mut Context<Self>) -> Effect<Command> {
+        let mut effects = Vec::new();
+
+        let watched_file = self
+            .loaded_input_data
+            .as_ref()
+            .filter(|_| settings::Settings::global(cx).live_reload)
+            .map(|data| data.file_path.to_path_buf());
+        if watched_file != self.watched_file {
+            self.watched_file = watched_file.clone();
+            let sub = watched_file.map(|path| Sub::watch_file(path, || Command::InputFileChanged));
+            effects.push(match sub {
+                Some(Ok(sub)) => Effect::subscribe(sub).with_id(LIVE_RELOAD_TASK),
+                Some(Err(e)) => {
+                    self.set_status(
+                        cx,
+                        Severity::Warning,
+                        t!("status.watch_failed", error = e).to_string(),
+                    );
+                    Effect::cancel(LIVE_RELOAD_TASK)
+                }
+                None => Effect::cancel(LIVE_RELOAD_TASK),
+            });
         }
+
+        Effect::batch(effects)
     }
 }
 
+/// Change the text of an input.
+fn set_input_value(input: &Entity<InputState>, value: impl Into<SharedString>) -> Effect<Command> {
+    let (input, value) = (input.clone(), value.into());
+    Effect::window(move |window, cx| {
+        input.update(cx, |input, cx| input.set_value(value, window, cx));
+    })
+}
+
 struct FirefoxSessionUtility {
+    focus_handle: FocusHandle,
     input_wizard: Entity<Wizard>,
+    tab_search: Entity<TabSearch>,
     new_input: Entity<InputState>,
     new_input_data: Option<rfd::FileHandle>,
+    recent_files: Entity<DropdownState<Vec<RecentFileValue>>>,
     loaded_input: Entity<InputState>,
     loaded_input_data: Option<host::FileInfo>,
     preview: Entity<InputState>,
     tab_group_list: Entity<List<TabGroupList>>,
+    /// Text used to select windows that match it.
+    select_matching: Entity<InputState>,
     output_path: Entity<InputState>,
     create_folder: bool,
     overwrite: bool,
     output_format: Entity<DropdownState<Vec<FormatInfoValue>>>,
-    status: Entity<InputState>,
+    /// Output format that is selected in the dropdown.
+    selected_format: Option<OutputFormat>,
+    /// User defined output formats.
+    templates: Vec<Arc<template::OutputTemplate>>,
+    template_editor: Entity<TemplateEditor>,
+    link_options: host::LinkOptions,
+    custom_page_break: Entity<InputState>,
+    show_output_options: bool,
+    browser_command: Entity<InputState>,
+    script_profile: Entity<InputState>,
+    open_tabs_confirm_limit: Entity<InputState>,
+    /// Latest message shown in the status bar.
+    status: SharedString,
+    status_log: StatusLog,
+    show_status_log: bool,
+    /// Show the panel with recorded messages.
+    show_inspector: bool,
+    /// File that is watched so that it can be reloaded when it changes.
+    watched_file: Option<PathBuf>,
+    history: History<UndoState>,
+    /// State after the latest recorded change. Compared to the current state
+    /// to detect new changes.
+    undo_baseline: Option<UndoState>,
+    _subscriptions: Vec<Subscription>,
 }
 impl FirefoxSessionUtility {
-    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
-        let new_input = cx.new(|cx: &mut Context<'_, _>| InputState::new(window, cx));
+    /// Create a view for a single session. If `input_path` is specified then
+    /// that file is loaded immediately.
+    pub fn new(window: &mut Window, cx: &mut Context<Self>, input_path: Option<PathBuf>) -> Self {
+        let settings = settings::Settings::global(cx).clone();
+
+        let new_input = cx.new(|cx: &mut Context<'_, _>| {
+            InputState::new(window, cx).default_value(
+                input_path
+                    .as_ref()
+                    .map(|path| path.to_string_lossy().into_owned())
+                    .or_else(|| settings.last_input_path.clone())
+                    .unwrap_or_default(),
+            )
+        });
         let loaded_input = cx.new(|cx: &mut Context<'_, _>| InputState::new(window, cx));
         let input_wizard = cx.new({
             let parent = cx.weak_entity();
             |cx| Wizard::new(window, cx, parent)
         });
+        let tab_search = cx.new({
+            let parent = cx.weak_entity();
+            |cx| TabSearch::new(window, cx, parent)
+        });
         let preview = cx.new(|cx: &mut Context<'_, _>| {
             InputState::new(window, cx).multi_line().searchable(true)
         });
@@ -574,139 +3150,925 @@ impl FirefoxSessionUtility {
                         tab_groups: Default::default(),
                         selected_tab_groups: Default::default(),
                         selected_item: None,
+                        anchor_item: None,
+                        expanded: HashSet::new(),
+                        rows: Default::default(),
+                        query: String::new(),
+                        search_tabs: true,
                     },
                     window,
                     cx,
                 )
-                .no_query()
             }
         });
 
+        let recent_files = cx.new(|cx: &mut Context<'_, _>| {
+            DropdownState::new(Self::recent_file_items(&settings), None, window, cx)
+        });
+
         let output_path = cx.new(|cx: &mut Context<'_, _>| {
-            InputState::new(window, cx).default_value({
-                #[cfg(windows)]
-                {
-                    std::env::var("USERPROFILE")
-                        .map(|home| home + r"\Downloads\firefox-links")
-                        .unwrap_or_default()
-                }
-                #[cfg(not(windows))]
-                {
-                    String::new()
-                }
-            })
+            InputState::new(window, cx).default_value(settings.output_path.clone())
+        });
+        let select_matching = cx.new(|cx: &mut Context<'_, _>| {
+            InputState::new(window, cx).placeholder(t!("sidebar.select_matching").to_string())
         });
 
+        let templates = settings
+            .templates
+            .iter()
+            .cloned()
+            .map(Arc::new)
+            .collect::<Vec<_>>();
+        let formats = Self::output_formats(&templates);
+        let selected = settings
+            .output_format
+            .as_ref()
+            .and_then(|id| formats.iter().position(|format| format.0.id() == id))
+            .or_else(|| {
+                formats
+                    .iter()
+                    .position(|format| format.0 == OutputFormat::Builtin(host::FormatInfo::PDF))
+            });
+        let selected_format = selected.map(|ix| formats[ix].0.clone());
         let output_format = cx.new(|cx: &mut Context<'_, _>| {
             DropdownState::new(
-                host::FormatInfo::all()
-                    .iter()
-                    .copied()
-                    .map(FormatInfoValue)
-                    .collect::<Vec<_>>(),
-                host::FormatInfo::all()
-                    .iter()
-                    .position(|fmt| *fmt == host::FormatInfo::PDF)
-                    .map(gpui_component::IndexPath::new),
+                formats,
+                selected.map(gpui_component::IndexPath::new),
                 window,
                 cx,
             )
         });
-        let status = cx.new(|cx: &mut Context<'_, _>| InputState::new(window, cx));
+        let template_editor = cx.new({
+            let parent = cx.weak_entity();
+            |cx| TemplateEditor::new(window, cx, parent)
+        });
+        let custom_page_break = cx.new(|cx: &mut Context<'_, _>| InputState::new(window, cx));
+        let browser_command = cx.new(|cx: &mut Context<'_, _>| {
+            InputState::new(window, cx)
+                .placeholder(t!("settings.default_browser").to_string())
+                .default_value(settings.browser_command.clone())
+        });
+        let script_profile = cx.new(|cx: &mut Context<'_, _>| {
+            InputState::new(window, cx)
+                .placeholder(t!("output.default_profile").to_string())
+                .default_value(settings.script_profile.clone())
+        });
+        let open_tabs_confirm_limit = cx.new(|cx: &mut Context<'_, _>| {
+            InputState::new(window, cx).default_value(settings.open_tabs_confirm_limit.to_string())
+        });
+
+        let _subscriptions = vec![
+            cx.subscribe_in(
+                &script_profile,
+                window,
+                |view, input, event: &InputEvent, window, cx| {
+                    if let InputEvent::Change { .. } = event {
+                        let mut settings = settings::Settings::global(cx).clone();
+                        settings.script_profile = input.read(cx).value().to_string();
+                        view.dispatch(window, cx, Command::SetSettings(settings));
+                        view.dispatch(window, cx, Command::RegeneratePreview);
+                    }
+                },
+            ),
+            cx.subscribe_in(
+                &browser_command,
+                window,
+                |view, input, event: &InputEvent, window, cx| {
+                    if let InputEvent::Change { .. } = event {
+                        let mut settings = settings::Settings::global(cx).clone();
+                        settings.browser_command = input.read(cx).value().to_string();
+                        view.dispatch(window, cx, Command::SetSettings(settings));
+                    }
+                },
+            ),
+            cx.subscribe_in(
+                &open_tabs_confirm_limit,
+                window,
+                |view, input, event: &InputEvent, window, cx| {
+                    if let InputEvent::Change { .. } = event {
+                        // Ignore invalid numbers while the user is typing:
+                        if let Ok(limit) = input.read(cx).value().trim().parse() {
+                            let mut settings = settings::Settings::global(cx).clone();
+                            settings.open_tabs_confirm_limit = limit;
+                            view.dispatch(window, cx, Command::SetSettings(settings));
+                        }
+                    }
+                },
+            ),
+            cx.subscribe_in(
+                &custom_page_break,
+                window,
+                |view, input, event: &InputEvent, window, cx| {
+                    if let InputEvent::Change { .. } = event {
+                        let mut link_options = view.link_options.clone();
+                        link_options.custom_page_break = input.read(cx).value().to_string();
+                        view.dispatch(window, cx, Command::SetLinkOptions(link_options));
+                    }
+                },
+            ),
+            cx.subscribe_in(
+                &select_matching,
+                window,
+                |view, input, event: &InputEvent, window, cx| {
+                    if let InputEvent::PressEnter { .. } = event {
+                        let text = input.read(cx).value().to_string();
+                        view.dispatch(
+                            window,
+                            cx,
+                            Command::BulkSelectTabGroups(BulkSelection::Matching(text)),
+                        );
+                    }
+                },
+            ),
+            cx.subscribe_in(
+                &recent_files,
+                window,
+                |view, recent_files, event: &DropdownEvent<Vec<RecentFileValue>>, window, cx| {
+                    let DropdownEvent::Confirm(Some(path)) = event else {
+                        return;
+                    };
+                    view.dispatch(window, cx, Command::SetInputPath(path.clone(), None));
+                    view.dispatch(window, cx, Command::LoadNewInputData);
+                    // Act like a menu and don't keep the selection:
+                    recent_files.update(cx, |recent_files, cx| {
+                        recent_files.set_selected_index(None, window, cx);
+                    });
+                },
+            ),
+            // Templates are used for the preview so regenerate it:
+            cx.subscribe_in(
+                &output_format,
+                window,
+                |view, _, event: &DropdownEvent<Vec<FormatInfoValue>>, window, cx| {
+                    if let DropdownEvent::Confirm(Some(format)) = event {
+                        view.dispatch(window, cx, Command::SetOutputFormat(format.clone()));
+                    }
+                },
+            ),
+            cx.observe_global_in::<settings::Settings>(window, |view, window, cx| {
+                view.dispatch(window, cx, Command::SettingsChanged);
+            }),
+        ];
+
+        if input_path.is_some() {
+            MsgSender::from_cx(window, cx)
+                .spawn(async move |_window, mut sender| {
+                    sender.send(Command::LoadNewInputData);
+                })
+                .detach();
+        }
+
+        let focus_handle = cx.focus_handle();
+        focus_handle.focus(window);
 
-        Self {
+        let mut view = Self {
+            focus_handle,
+            tab_search,
             new_input,
             new_input_data: None,
+            recent_files,
             loaded_input,
             loaded_input_data: None,
             input_wizard,
             preview,
             tab_group_list,
-            create_folder: false,
-            overwrite: false,
+            select_matching,
+            create_folder: settings.create_folder,
+            overwrite: settings.overwrite,
             output_path,
             output_format,
-            status,
+            selected_format,
+            templates,
+            template_editor,
+            link_options: Default::default(),
+            custom_page_break,
+            show_output_options: false,
+            browser_command,
+            script_profile,
+            open_tabs_confirm_limit,
+            status: SharedString::default(),
+            status_log: StatusLog::default(),
+            show_status_log: false,
+            show_inspector: false,
+            watched_file: None,
+            history: History::default(),
+            undo_baseline: None,
+            _subscriptions,
+        };
+        view.undo_baseline = Some(view.undo_state(cx));
+        view
+    }
+
+    fn undo_state(&self, cx: &App) -> UndoState {
+        UndoState {
+            selection: self
+                .tab_group_list
+                .read(cx)
+                .delegate()
+                .selected_tab_groups
+                .clone(),
+            link_options: self.link_options.clone(),
+            output_format: self.selected_format.clone(),
+            templates: self.templates.clone(),
+            create_folder: self.create_folder,
+            overwrite: self.overwrite,
+        }
+    }
+
+    /// Add an undo step if anything changed since the last one.
+    fn record_undo_state(&mut self, cx: &App) {
+        let current = self.undo_state(cx);
+        match self.undo_baseline.replace(current.clone()) {
+            Some(previous) if previous != current => self.history.record(previous),
+            _ => {}
+        }
+    }
+
+    fn restore_undo_state(&mut self, cx: &mut Context<Self>, state: UndoState) -> Effect<Command> {
+        self.tab_group_list.update(cx, |list, cx| {
+            list.delegate_mut().selected_tab_groups = state.selection.clone();
+            cx.notify();
+        });
+        self.link_options = state.link_options.clone();
+        self.create_folder = state.create_folder;
+        self.overwrite = state.overwrite;
+        self.templates = state.templates.clone();
+        let mut effects = vec![self.update_output_formats(cx, state.output_format.clone())];
+        let page_break = &state.link_options.custom_page_break;
+        if self.custom_page_break.read(cx).value().as_str() != page_break {
+            effects.push(set_input_value(&self.custom_page_break, page_break.clone()));
+        }
+        self.undo_baseline = Some(state);
+        cx.notify();
+        effects.push(self.save_settings(cx));
+        Effect::batch(effects)
+    }
+
+    /// Remember the output choices of this session tab in the settings.
+    fn save_settings(&mut self, cx: &mut Context<Self>) -> Effect<Command> {
+        let output_path = self.output_path.read(cx).value().to_string();
+        let (create_folder, overwrite) = (self.create_folder, self.overwrite);
+        let output_format = self
+            .selected_format
+            .as_ref()
+            .map(|format| format.id().to_string());
+        let templates = self
+            .templates
+            .iter()
+            .map(|template| (**template).clone())
+            .collect();
+        self.update_settings(cx, |settings| {
+            settings.output_path = output_path;
+            settings.create_folder = create_folder;
+            settings.overwrite = overwrite;
+            settings.output_format = output_format;
+            settings.templates = templates;
+        })
+    }
+
+    /// Change the settings that are shared by all session tabs. The returned
+    /// effect writes them to the settings file once they stop changing.
+    /// Session tabs update themselves in [`Command::SettingsChanged`].
+    fn update_settings(
+        &mut self,
+        cx: &mut Context<Self>,
+        f: impl FnOnce(&mut settings::Settings),
+    ) -> Effect<Command> {
+        let mut settings = settings::Settings::global(cx).clone();
+        f(&mut settings);
+        if settings == *settings::Settings::global(cx) {
+            return Effect::none();
+        }
+        cx.set_global(settings);
+        Effect::send_later(SAVE_SETTINGS_DELAY, Command::SaveSettings).with_id(SAVE_SETTINGS_TASK)
+    }
+
+    fn output_formats(templates: &[Arc<template::OutputTemplate>]) -> Vec<FormatInfoValue> {
+        host::FormatInfo::all()
+            .iter()
+            .copied()
+            .map(OutputFormat::Builtin)
+            .chain(
+                script::ScriptKind::all()
+                    .iter()
+                    .copied()
+                    .map(OutputFormat::Script),
+            )
+            .chain(templates.iter().cloned().map(OutputFormat::Template))
+            .map(FormatInfoValue)
+            .collect()
+    }
+
+    fn open_tab_search(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let tab_groups = self.tab_group_list.read(cx).delegate().tab_groups.clone();
+        TabSearch::open_modal(window, cx, self.tab_search.downgrade(), &tab_groups);
+    }
+
+    /// Buttons that change the selection of many windows at once.
+    fn render_selection_toolbar(&self, cx: &mut Context<'_, Self>) -> impl IntoElement {
+        let button = |id: &'static str, label: Cow<'static, str>, selection: BulkSelection| {
+            Button::new(id)
+                .label(label.into_owned())
+                .small()
+                .ghost()
+                .on_click(cx.listener(move |view, _, window, cx| {
+                    view.dispatch(window, cx, Command::BulkSelectTabGroups(selection.clone()));
+                }))
+        };
+        v_flex()
+            .p_1()
+            .gap_1()
+            .text_sm()
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(button(
+                        "select-all-windows",
+                        t!("sidebar.select_all"),
+                        BulkSelection::All,
+                    ))
+                    .child(button(
+                        "select-no-windows",
+                        t!("sidebar.select_none"),
+                        BulkSelection::None,
+                    ))
+                    .child(button(
+                        "invert-window-selection",
+                        t!("sidebar.invert"),
+                        BulkSelection::Invert,
+                    ))
+                    .child(button(
+                        "select-closed-windows",
+                        t!("sidebar.select_closed"),
+                        BulkSelection::AllClosed,
+                    )),
+            )
+            .child(TextInput::new(&self.select_matching).small())
+            .child(
+                Checkbox::new("search-tabs")
+                    .label(t!("sidebar.search_tabs").to_string())
+                    .checked(self.tab_group_list.read(cx).delegate().search_tabs)
+                    .on_click(cx.listener(|view, checked, _window, cx| {
+                        view.tab_group_list.update(cx, |list, cx| {
+                            list.delegate_mut().set_search_tabs(*checked);
+                            cx.notify();
+                        });
+                    })),
+            )
+    }
+
+    /// Select the next or previous output format.
+    fn cycle_output_format(&mut self, window: &mut Window, cx: &mut Context<Self>, forward: bool) {
+        let formats = Self::output_formats(&self.templates);
+        if formats.is_empty() {
+            return;
+        }
+        let current = self.selected_format.as_ref();
+        let index = formats
+            .iter()
+            .position(|format| Some(&format.0) == current)
+            .map_or(0, |index| {
+                if forward {
+                    (index + 1) % formats.len()
+                } else {
+                    (index + formats.len() - 1) % formats.len()
+                }
+            });
+        let format = formats[index].0.clone();
+        self.dispatch(window, cx, Command::SetOutputFormat(format));
+    }
+
+    /// Update text that was stored in child views after the language changed.
+    fn update_translations(&mut self, cx: &mut Context<Self>) -> Effect<Command> {
+        let placeholders = [
+            (self.select_matching.clone(), t!("sidebar.select_matching")),
+            (self.browser_command.clone(), t!("settings.default_browser")),
+            (self.script_profile.clone(), t!("output.default_profile")),
+        ];
+        Effect::batch([
+            Effect::window(move |window, cx| {
+                for (input, placeholder) in placeholders {
+                    input.update(cx, |input, cx| {
+                        input.set_placeholder(placeholder.into_owned(), window, cx);
+                    });
+                }
+            }),
+            self.update_output_formats(cx, None),
+        ])
+    }
+
+    /// Name of the loaded file, shown in the session's tab.
+    fn title(&self, cx: &App) -> SharedString {
+        let loaded = self.loaded_input.read(cx).value();
+        match Path::new(loaded.as_str()).file_name() {
+            Some(file_name) => file_name.to_string_lossy().into_owned().into(),
+            None => t!("session.new").to_string().into(),
+        }
+    }
+
+    fn recent_file_items(settings: &settings::Settings) -> Vec<RecentFileValue> {
+        settings
+            .recent_files
+            .iter()
+            .cloned()
+            .map(RecentFileValue::new)
+            .collect()
+    }
+
+    fn update_recent_files(&self, cx: &App) -> Effect<Command> {
+        let items = Self::recent_file_items(settings::Settings::global(cx));
+        let recent_files = self.recent_files.clone();
+        Effect::window(move |window, cx| {
+            recent_files.update(cx, |recent_files, cx| {
+                recent_files.set_items(items, window, cx);
+            });
+        })
+    }
+
+    /// Update the output format dropdown after the templates have changed.
+    fn update_output_formats(
+        &mut self,
+        cx: &mut Context<Self>,
+        select: Option<OutputFormat>,
+    ) -> Effect<Command> {
+        if select.is_some() {
+            self.selected_format = select;
         }
+        cx.notify();
+        self.show_output_format(Some(Self::output_formats(&self.templates)))
+    }
+
+    /// Show the selected output format in the dropdown, after replacing the
+    /// formats that it lists if `formats` is specified.
+    fn show_output_format(&self, formats: Option<Vec<FormatInfoValue>>) -> Effect<Command> {
+        let (dropdown, selected) = (self.output_format.clone(), self.selected_format.clone());
+        Effect::window(move |window, cx| {
+            dropdown.update(cx, |dropdown, cx| {
+                if let Some(formats) = formats {
+                    dropdown.set_items(formats, window, cx);
+                }
+                if let Some(selected) = &selected {
+                    dropdown.set_selected_value(selected, window, cx);
+                }
+            });
+        })
     }
 
     pub fn set_status(
         &mut self,
-        window: &mut Window,
-        cx: &mut impl AppContext,
+        cx: &mut Context<Self>,
+        severity: Severity,
         new_status: impl Into<SharedString>,
     ) {
-        self.status.update(cx, |status, cx| {
-            status.set_value(new_status, window, cx);
-        });
+        let new_status = new_status.into();
+        self.status_log.push(severity, new_status.to_string());
+        self.status = new_status;
+        cx.notify();
+    }
+
+    /// Collapsible panel below the status bar that lists earlier statuses.
+    fn render_status_log(&self, cx: &mut Context<'_, Self>) -> impl IntoElement {
+        v_flex()
+            .h(px(200.))
+            .border_t_1()
+            .border_color(cx.theme().border)
+            .child(
+                h_flex()
+                    .py_1()
+                    .gap_2()
+                    .child(Label::new(t!("status_log.title").to_string()).font_semibold())
+                    .child(div().flex_grow())
+                    .child(
+                        Button::new("export-status-log")
+                            .label(t!("status_log.export").to_string())
+                            .small()
+                            .ghost()
+                            .disabled(self.status_log.entries().is_empty())
+                            .on_click(cx.listener(|view, _, window, cx| {
+                                view.dispatch(window, cx, Command::ExportStatusLog);
+                            })),
+                    )
+                    .child(
+                        Button::new("clear-status-log")
+                            .label(t!("status_log.clear").to_string())
+                            .small()
+                            .ghost()
+                            .disabled(self.status_log.entries().is_empty())
+                            .on_click(cx.listener(|view, _, window, cx| {
+                                view.dispatch(window, cx, Command::ClearStatusLog);
+                            })),
+                    ),
+            )
+            .when(self.status_log.entries().is_empty(), |this| {
+                this.child(
+                    Label::new(t!("status_log.empty").to_string())
+                        .text_sm()
+                        .text_color(cx.theme().muted_foreground),
+                )
+            })
+            .child(
+                // Only the visible rows are rendered since the log can be long:
+                uniform_list(
+                    "status-log-entries",
+                    self.status_log.entries().len(),
+                    cx.processor(|view, range, _window, cx| view.render_status_entries(range, cx)),
+                )
+                .flex_1()
+                .text_sm(),
+            )
+    }
+
+    /// Rows of the status log, newest entries first.
+    fn render_status_entries(
+        &self,
+        range: Range<usize>,
+        cx: &mut Context<'_, Self>,
+    ) -> Vec<impl IntoElement> {
+        let color = |severity: Severity| match severity {
+            Severity::Info => cx.theme().muted_foreground,
+            Severity::Success => cx.theme().success,
+            Severity::Warning => cx.theme().warning,
+            Severity::Error => cx.theme().danger,
+        };
+        let entries = self.status_log.entries();
+        range
+            .filter_map(|ix| entries.get(entries.len().checked_sub(ix + 1)?))
+            .map(|entry| {
+                h_flex()
+                    .gap_2()
+                    .items_start()
+                    .child(
+                        div()
+                            .flex_none()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(entry.short_time()),
+                    )
+                    .child(
+                        div()
+                            .flex_none()
+                            .w(px(80.))
+                            .text_color(color(entry.severity))
+                            .child(entry.severity.title()),
+                    )
+                    // A single line so that all rows have the same height:
+                    .child(div().flex_1().truncate().child(entry.message.clone()))
+            })
+            .collect()
+    }
+
+    /// Collapsible panel below the status bar that lists recorded messages.
+    fn render_inspector(&self, cx: &mut Context<'_, Self>) -> impl IntoElement {
+        let recording = trace::is_recording(cx);
+        let entries = trace::entries(cx, cx.entity_id());
+        v_flex()
+            .h(px(240.))
+            .border_t_1()
+            .border_color(cx.theme().border)
+            .child(
+                h_flex()
+                    .py_1()
+                    .gap_2()
+                    .child(Label::new(t!("inspector.title").to_string()).font_semibold())
+                    .child(div().flex_grow())
+                    .child(
+                        Button::new("toggle-recording")
+                            .label(if recording {
+                                t!("inspector.stop").to_string()
+                            } else {
+                                t!("inspector.record").to_string()
+                            })
+                            .small()
+                            .selected(recording)
+                            .on_click(cx.listener(|view, _, window, cx| {
+                                view.dispatch(window, cx, Command::ToggleRecording);
+                            })),
+                    )
+                    .child(
+                        Button::new("save-recording")
+                            .label(t!("inspector.save").to_string())
+                            .small()
+                            .ghost()
+                            .disabled(entries.is_empty())
+                            .on_click(cx.listener(|view, _, window, cx| {
+                                view.dispatch(window, cx, Command::SaveRecording);
+                            })),
+                    )
+                    .child(
+                        Button::new("open-recording")
+                            .label(t!("inspector.replay").to_string())
+                            .small()
+                            .ghost()
+                            .on_click(cx.listener(|view, _, window, cx| {
+                                view.dispatch(window, cx, Command::OpenRecording);
+                            })),
+                    )
+                    .child(
+                        Button::new("clear-recording")
+                            .label(t!("inspector.clear").to_string())
+                            .small()
+                            .ghost()
+                            .disabled(entries.is_empty())
+                            .on_click(cx.listener(|view, _, window, cx| {
+                                view.dispatch(window, cx, Command::ClearRecording);
+                            })),
+                    ),
+            )
+            .when(entries.is_empty(), |this| {
+                this.child(
+                    Label::new(t!("inspector.empty").to_string())
+                        .text_sm()
+                        .text_color(cx.theme().muted_foreground),
+                )
+            })
+            .child(
+                // Only the visible rows are rendered since there can be many
+                // entries:
+                uniform_list(
+                    "inspector-entries",
+                    entries.len(),
+                    cx.processor(|view, range, _window, cx| view.render_trace_entries(range, cx)),
+                )
+                .flex_1()
+                .text_sm(),
+            )
+    }
+
+    /// Rows of the inspector, newest entries first.
+    fn render_trace_entries(
+        &self,
+        range: Range<usize>,
+        cx: &mut Context<'_, Self>,
+    ) -> Vec<impl IntoElement> {
+        let entries = trace::entries(cx, cx.entity_id());
+        range
+            .filter_map(|ix| entries.get(entries.len().checked_sub(ix + 1)?))
+            .map(|entry| {
+                h_flex()
+                    .gap_2()
+                    .items_start()
+                    .child(
+                        div()
+                            .flex_none()
+                            .w(px(60.))
+                            .text_color(cx.theme().muted_foreground)
+                            .child(entry.short_time()),
+                    )
+                    .child(
+                        div()
+                            .flex_none()
+                            .w(px(50.))
+                            .text_color(cx.theme().muted_foreground)
+                            .child(entry.source.as_str()),
+                    )
+                    // Single lines so that all rows have the same height:
+                    .child(
+                        v_flex()
+                            .flex_1()
+                            .overflow_hidden()
+                            .child(div().truncate().child(entry.description.clone()))
+                            .child(
+                                div()
+                                    .truncate()
+                                    .text_xs()
+                                    .text_color(cx.theme().muted_foreground)
+                                    .child(entry.state.clone()),
+                            ),
+                    )
+            })
+            .collect()
+    }
+
+    /// Prompt for a sessionstore file to load.
+    fn browse_input_path() -> Effect<Command> {
+        Effect::with_window(|window, _cx| Self::prompt_input_path(window))
+    }
+
+    fn prompt_input_path(window: &mut Window) -> Effect<Command> {
+        /*
+        let prompt =
+            cx.prompt_for_paths(gpui::PathPromptOptions {
+                files: true,
+                directories: false,
+                multiple: false,
+                prompt: Some(
+                    "Select Firefox Sessionstore File".into(),
+                ),
+            });
+        let prompt = async move {
+            let mut selected = prompt.await.unwrap().unwrap()?;
+            let first = selected.remove(0);
+            assert_eq!(selected.len(), 0);
+            Some(first)
+        };
+        // */
+        let prompt = host::prompt_load_file(Some(&host::NoDisplayHandle(&*window)));
+        let prompt = async move {
+            let file = prompt.await?;
+            Some(Command::SetInputPath(
+                file.path().to_string_lossy().into_owned(),
+                Some(file),
+            ))
+        };
+
+        Effect::spawn(|mut sender| async move {
+            if let Some(command) = prompt.await {
+                sender.send(command);
+            }
+        })
+    }
+
+    /// Prompt for a file path to save links to.
+    fn browse_save_path() -> Effect<Command> {
+        Effect::with_window(|window, _cx| Self::prompt_save_path(window))
     }
 
-    fn input_browse_event_listener(
-        window: &mut Window,
-        cx: &mut Context<'_, Self>,
-    ) -> impl Fn(&gpui::ClickEvent, &mut Window, &mut App) {
-        let sender = MsgSender::from_cx(window, cx);
-        move |_, window, _cx| {
-            /*
-            let prompt =
-                cx.prompt_for_paths(gpui::PathPromptOptions {
-                    files: true,
-                    directories: false,
-                    multiple: false,
-                    prompt: Some(
-                        "Select Firefox Sessionstore File".into(),
-                    ),
-                });
-            let prompt = async move {
-                let mut selected = prompt.await.unwrap().unwrap()?;
-                let first = selected.remove(0);
-                assert_eq!(selected.len(), 0);
-                Some(first)
-            };
-            // */
-            let prompt = host::prompt_load_file(Some(&host::NoDisplayHandle(&*window)));
-            let prompt = async move {
-                let file = prompt.await?;
-                Some(Command::SetInputPath(
-                    file.path().to_string_lossy().into_owned(),
-                    Some(file),
-                ))
-            };
+    fn prompt_save_path(window: &mut Window) -> Effect<Command> {
+        // let prompt =
+        //     cx.prompt_for_new_path("".as_ref(), None);
+        // let prompt = async move { prompt.await.unwrap().unwrap() };
 
-            sender
-                .spawn(async move |_window, mut sender| {
-                    if let Some(command) = prompt.await {
-                        sender.send(command);
-                    }
-                })
-                .detach();
-        }
+        let prompt = host::prompt_save_file(Some(&host::NoDisplayHandle(&*window)));
+        let prompt = async move {
+            Some(Command::SetSavePath(
+                prompt.await?.path().to_string_lossy().into_owned(),
+            ))
+        };
+
+        Effect::spawn(|mut sender| async move {
+            if let Some(command) = prompt.await {
+                sender.send(command);
+            }
+        })
     }
 
-    fn output_browse_event_listener(
-        window: &mut Window,
-        cx: &mut Context<'_, Self>,
-    ) -> impl Fn(&gpui::ClickEvent, &mut Window, &mut App) {
-        let sender = MsgSender::from_cx(window, cx);
-        move |_, window, _cx| {
-            // let prompt =
-            //     cx.prompt_for_new_path("".as_ref(), None);
-            // let prompt = async move { prompt.await.unwrap().unwrap() };
-
-            let prompt = host::prompt_save_file(Some(&host::NoDisplayHandle(&*window)));
-            let prompt = async move {
-                Some(Command::SetSavePath(
-                    prompt.await?.path().to_string_lossy().into_owned(),
-                ))
-            };
+    /// Options that affect how tabs are converted to links.
+    fn render_output_options(&self, cx: &mut Context<'_, Self>) -> impl IntoElement {
+        let options = &self.link_options;
+        let table_of_content = self
+            .tab_group_list
+            .read(cx)
+            .delegate()
+            .selected_tab_groups
+            .table_of_content;
 
-            sender
-                .spawn(async move |_window, mut sender| {
-                    if let Some(command) = prompt.await {
-                        sender.send(command);
-                    }
-                })
-                .detach();
+        // Enabled tree sources in priority order followed by disabled ones:
+        let tree_sources = options
+            .tree_sources
+            .iter()
+            .copied()
+            .map(|source| (source, true))
+            .chain(
+                host::TreeSource::all()
+                    .iter()
+                    .copied()
+                    .filter(|source| !options.tree_sources.contains(source))
+                    .map(|source| (source, false)),
+            )
+            .enumerate()
+            .map(|(ix, (source, enabled))| {
+                h_flex()
+                    .gap_2()
+                    .child(
+                        Checkbox::new(("tree-source", ix))
+                            .label(source.title())
+                            .checked(enabled)
+                            .on_click(cx.listener(move |view, checked, window, cx| {
+                                let mut options = view.link_options.clone();
+                                options.tree_sources.retain(|v| *v != source);
+                                if *checked {
+                                    options.tree_sources.push(source);
+                                }
+                                view.dispatch(window, cx, Command::SetLinkOptions(options));
+                            })),
+                    )
+                    .when(enabled, |this| {
+                        this.child(
+                            Button::new(("tree-source-up", ix))
+                                .icon(IconName::ArrowUp)
+                                .disabled(ix == 0)
+                                .on_click(cx.listener(move |view, _, window, cx| {
+                                    let mut options = view.link_options.clone();
+                                    options.tree_sources.swap(ix - 1, ix);
+                                    view.dispatch(window, cx, Command::SetLinkOptions(options));
+                                })),
+                        )
+                        .child(
+                            Button::new(("tree-source-down", ix))
+                                .icon(IconName::ArrowDown)
+                                .disabled(ix + 1 >= options.tree_sources.len())
+                                .on_click(cx.listener(move |view, _, window, cx| {
+                                    let mut options = view.link_options.clone();
+                                    options.tree_sources.swap(ix, ix + 1);
+                                    view.dispatch(window, cx, Command::SetLinkOptions(options));
+                                })),
+                        )
+                    })
+            })
+            .collect::<Vec<_>>();
+
+        GroupBox::new()
+            .content_style(StyleRefinement::default().py_2().px_2().border_2())
+            .outline()
+            .child(
+                h_flex()
+                    .gap_8()
+                    .items_start()
+                    .child(
+                        v_flex()
+                            .gap_2()
+                            .child(
+                                Checkbox::new("output-table-of-content")
+                                    .label(t!("output.table_of_contents").to_string())
+                                    .checked(table_of_content)
+                                    .on_click(cx.listener(|view, checked, window, cx| {
+                                        view.dispatch(
+                                            window,
+                                            cx,
+                                            Command::SetTableOfContents(*checked),
+                                        );
+                                    })),
+                            )
+                            .child(
+                                Checkbox::new("output-indent-all-links")
+                                    .label(t!("output.indent_all_links").to_string())
+                                    .checked(options.indent_all_links)
+                                    .on_click(cx.listener(|view, checked, window, cx| {
+                                        let mut options = view.link_options.clone();
+                                        options.indent_all_links = *checked;
+                                        view.dispatch(window, cx, Command::SetLinkOptions(options));
+                                    })),
+                            )
+                            .child(
+                                Checkbox::new("output-default-page-breaks")
+                                    .label(t!("output.default_page_breaks").to_string())
+                                    .checked(options.page_breaks_after_group.is_none())
+                                    .on_click(cx.listener(|view, checked, window, cx| {
+                                        let mut options = view.link_options.clone();
+                                        options.page_breaks_after_group =
+                                            if *checked { None } else { Some(true) };
+                                        view.dispatch(window, cx, Command::SetLinkOptions(options));
+                                    })),
+                            )
+                            .child(
+                                Checkbox::new("output-page-breaks")
+                                    .label(t!("output.page_break_after_window").to_string())
+                                    .disabled(options.page_breaks_after_group.is_none())
+                                    .checked(options.page_breaks_after_group.unwrap_or(false))
+                                    .on_click(cx.listener(|view, checked, window, cx| {
+                                        let mut options = view.link_options.clone();
+                                        options.page_breaks_after_group = Some(*checked);
+                                        view.dispatch(window, cx, Command::SetLinkOptions(options));
+                                    })),
+                            )
+                            .child(
+                                Checkbox::new("output-skip-last-page-break")
+                                    .label(t!("output.skip_last_page_break").to_string())
+                                    .checked(options.skip_page_break_after_last_group)
+                                    .on_click(cx.listener(|view, checked, window, cx| {
+                                        let mut options = view.link_options.clone();
+                                        options.skip_page_break_after_last_group = *checked;
+                                        view.dispatch(window, cx, Command::SetLinkOptions(options));
+                                    })),
+                            )
+                            .child(
+                                h_flex()
+                                    .child(t!("output.custom_page_break").to_string())
+                                    .child(TextInput::new(&self.custom_page_break).ml_2()),
+                            )
+                            .child(
+                                h_flex()
+                                    .child(t!("output.script_profile").to_string())
+                                    .child(TextInput::new(&self.script_profile).ml_2()),
+                            ),
+                    )
+                    .child(
+                        v_flex()
+                            .gap_2()
+                            .child(Label::new(t!("output.tree_sources").to_string()))
+                            .children(tree_sources),
+                    ),
+            )
+    }
+
+    /// Generate output for formats that are written by this program instead of
+    /// `firefox_session_data`. Returns the text and its file extension.
+    fn render_text_output(&self, format: &OutputFormat, cx: &App) -> Option<(String, String)> {
+        let list = self.tab_group_list.read(cx).delegate();
+        let (tab_groups, options) = (&list.tab_groups, &list.selected_tab_groups);
+        match format {
+            OutputFormat::Builtin(_) => None,
+            OutputFormat::Template(template) => Some((
+                template.render(tab_groups, options),
+                template.file_extension.clone(),
+            )),
+            OutputFormat::Script(kind) => Some((
+                kind.render(
+                    tab_groups,
+                    options,
+                    &settings::Settings::global(cx).script_profile,
+                ),
+                kind.file_extension().to_owned(),
+            )),
         }
     }
 
@@ -719,12 +4081,11 @@ impl FirefoxSessionUtility {
         move |window, cx| {
             let output_format = view
                 .upgrade()
-                .and_then(|view| view.read(cx).output_format.read(cx).selected_value())
-                .copied();
+                .and_then(|view| view.read(cx).selected_format.clone());
             let info = if let Some(output_format) = output_format {
-                SharedString::from(output_format.to_string())
+                output_format.description()
             } else {
-                "No output format selected.".into()
+                t!("output.no_format").to_string().into()
             };
             Tooltip::element(move |window, cx| {
                 TextView::markdown("output-format-tooltip", info.clone(), window, cx)
@@ -736,13 +4097,161 @@ impl FirefoxSessionUtility {
 impl Render for FirefoxSessionUtility {
     fn render(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) -> impl IntoElement {
         let modal_layer = Root::render_modal_layer(window, cx);
+        let notification_layer = Root::render_notification_layer(window, cx);
+        let selected_template = self
+            .selected_format
+            .as_ref()
+            .and_then(OutputFormat::as_template)
+            .cloned();
 
         h_flex()
             .size_full()
+            .key_context(actions::MAIN_CONTEXT)
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(|view, _: &actions::BrowseInput, window, cx| {
+                view.dispatch(window, cx, Command::BrowseInputPath);
+            }))
+            .on_action(cx.listener(|view, _: &actions::LoadInput, window, cx| {
+                view.dispatch(window, cx, Command::LoadNewInputData);
+            }))
+            .on_action(cx.listener(|view, _: &actions::ReloadInput, window, cx| {
+                view.dispatch(window, cx, Command::ReloadInputData);
+            }))
+            .on_action(cx.listener(|view, _: &actions::CopyLinks, window, cx| {
+                view.dispatch(window, cx, Command::CopyLinksToClipboard);
+            }))
+            .on_action(cx.listener(|view, _: &actions::BrowseOutput, window, cx| {
+                view.dispatch(window, cx, Command::BrowseSavePath);
+            }))
+            .on_action(cx.listener(|view, _: &actions::SaveLinks, window, cx| {
+                view.dispatch(window, cx, Command::SaveLinksToFile);
+            }))
+            .on_action(cx.listener(|view, _: &actions::PreviewPdf, window, cx| {
+                view.dispatch(window, cx, Command::GeneratePdfPreview);
+            }))
+            .on_action(
+                cx.listener(|view, _: &actions::ToggleWindowSelection, window, cx| {
+                    view.tab_group_list.update(cx, |list, cx| {
+                        list.delegate_mut().confirm(false, window, cx);
+                    });
+                }),
+            )
+            .on_action(
+                cx.listener(|view, _: &actions::SelectAllWindows, window, cx| {
+                    view.dispatch(window, cx, Command::BulkSelectTabGroups(BulkSelection::All));
+                }),
+            )
+            .on_action(
+                cx.listener(|view, _: &actions::SelectNoWindows, window, cx| {
+                    view.dispatch(
+                        window,
+                        cx,
+                        Command::BulkSelectTabGroups(BulkSelection::None),
+                    );
+                }),
+            )
+            .on_action(
+                cx.listener(|view, _: &actions::InvertSelection, window, cx| {
+                    view.dispatch(
+                        window,
+                        cx,
+                        Command::BulkSelectTabGroups(BulkSelection::Invert),
+                    );
+                }),
+            )
+            .on_action(
+                cx.listener(|view, _: &actions::NextOutputFormat, window, cx| {
+                    view.cycle_output_format(window, cx, true);
+                }),
+            )
+            .on_action(
+                cx.listener(|view, _: &actions::PreviousOutputFormat, window, cx| {
+                    view.cycle_output_format(window, cx, false);
+                }),
+            )
+            .on_action(
+                cx.listener(|view, _: &actions::ToggleOutputOptions, window, cx| {
+                    view.dispatch(window, cx, Command::ToggleOutputOptions);
+                }),
+            )
+            .on_action(cx.listener(|view, _: &actions::OpenInBrowser, window, cx| {
+                view.dispatch(window, cx, Command::OpenSelectedTabs);
+            }))
+            .on_action(cx.listener(|view, _: &actions::Undo, window, cx| {
+                view.dispatch(window, cx, Command::Undo);
+            }))
+            .on_action(cx.listener(|view, _: &actions::Redo, window, cx| {
+                view.dispatch(window, cx, Command::Redo);
+            }))
+            .on_action(
+                cx.listener(|view, _: &actions::ToggleStatusLog, window, cx| {
+                    view.dispatch(window, cx, Command::ToggleStatusLog);
+                }),
+            )
+            .on_action(
+                cx.listener(|view, _: &actions::ToggleInspector, window, cx| {
+                    view.dispatch(window, cx, Command::ToggleInspector);
+                }),
+            )
+            .on_action(cx.listener(|view, _: &actions::SearchTabs, window, cx| {
+                view.open_tab_search(window, cx);
+            }))
+            .on_action(cx.listener(|_, _: &actions::OpenSettings, window, cx| {
+                let view = cx.weak_entity();
+                SettingsPanel::open_modal(window, cx, view);
+            }))
+            .on_action(cx.listener(|view, _: &actions::CycleTheme, window, cx| {
+                let mut settings = settings::Settings::global(cx).clone();
+                settings.theme = settings.theme.next();
+                view.dispatch(window, cx, Command::SetSettings(settings));
+            }))
+            .on_action(
+                cx.listener(|view, _: &actions::IncreaseFontSize, window, cx| {
+                    let mut settings = settings::Settings::global(cx).clone();
+                    settings.change_font_size(1.);
+                    view.dispatch(window, cx, Command::SetSettings(settings));
+                }),
+            )
+            .on_action(
+                cx.listener(|view, _: &actions::DecreaseFontSize, window, cx| {
+                    let mut settings = settings::Settings::global(cx).clone();
+                    settings.change_font_size(-1.);
+                    view.dispatch(window, cx, Command::SetSettings(settings));
+                }),
+            )
+            .on_action(cx.listener(|view, _: &actions::ResetFontSize, window, cx| {
+                let mut settings = settings::Settings::global(cx).clone();
+                settings.font_size = settings::DEFAULT_FONT_SIZE;
+                view.dispatch(window, cx, Command::SetSettings(settings));
+            }))
+            // Load session files that are dropped onto the window:
+            .drag_over::<ExternalPaths>(|style, _, _, cx| style.bg(cx.theme().drop_target))
+            .on_drop(cx.listener(|view, paths: &ExternalPaths, window, cx| {
+                let Some(path) = paths
+                    .paths()
+                    .iter()
+                    .find(|path| host::is_session_file(path))
+                else {
+                    view.set_status(
+                        cx,
+                        Severity::Error,
+                        t!(
+                            "status.not_session_files",
+                            extensions = host::SESSION_FILE_EXTENSIONS.join(", ")
+                        )
+                        .to_string(),
+                    );
+                    return;
+                };
+                let path = path.to_string_lossy().into_owned();
+                view.dispatch(window, cx, Command::SetInputPath(path, None));
+                view.dispatch(window, cx, Command::LoadNewInputData);
+            }))
             // Sidebar (select windows/groups):
             .child(
                 div()
                     .flex()
+                    .key_context(actions::SIDEBAR_CONTEXT)
                     //.bg(rgb(0x2e7d32))
                     .h_full()
                     .w(Pixels::from(250.0))
@@ -750,7 +4259,12 @@ impl Render for FirefoxSessionUtility {
                     .items_center()
                     .text_xl()
                     //.text_color(rgb(0xffffff))
-                    .child(self.tab_group_list.clone()),
+                    .child(
+                        v_flex()
+                            .size_full()
+                            .child(self.render_selection_toolbar(cx))
+                            .child(self.tab_group_list.clone()),
+                    ),
             )
             // Main view:
             .child(
@@ -762,8 +4276,16 @@ impl Render for FirefoxSessionUtility {
                     .child(
                         h_flex()
                             .my_2()
-                            .child("Path to sessionstore file:")
+                            .child(t!("input.path").to_string())
                             .child(TextInput::new(&self.new_input).ml_2())
+                            .child(
+                                Dropdown::new(&self.recent_files)
+                                    .placeholder(t!("input.recent_files").to_string())
+                                    .empty(Label::new(t!("input.no_recent_files").to_string()))
+                                    .menu_width(px(400.))
+                                    .w(px(150.))
+                                    .ml_2(),
+                            )
                             .child(
                                 Button::new("input-wizard")
                                     .on_click({
@@ -772,48 +4294,90 @@ impl Render for FirefoxSessionUtility {
                                             Wizard::open_modal(window, cx, view.clone());
                                         }
                                     })
-                                    .child("Wizard")
+                                    .child(t!("input.wizard").to_string())
                                     .ml_2(),
                             )
                             .child(
                                 Button::new("input-browse")
-                                    .on_click(Self::input_browse_event_listener(window, cx))
-                                    .child("Browse")
+                                    .on_click(cx.listener(|view, _, window, cx| {
+                                        view.dispatch(window, cx, Command::BrowseInputPath);
+                                    }))
+                                    .child(t!("common.browse").to_string())
+                                    .ml_2(),
+                            )
+                            .child(
+                                Button::new("open-tab-search")
+                                    .icon(IconName::Search)
+                                    .tooltip(t!("input.search_tabs").to_string())
+                                    .on_click(cx.listener(|view, _, window, cx| {
+                                        view.open_tab_search(window, cx);
+                                    }))
+                                    .ml_2(),
+                            )
+                            .child(
+                                Button::new("open-settings")
+                                    .icon(IconName::Settings)
+                                    .tooltip(t!("input.settings").to_string())
+                                    .on_click({
+                                        let view = cx.weak_entity();
+                                        move |_, window, cx| {
+                                            SettingsPanel::open_modal(window, cx, view.clone());
+                                        }
+                                    })
                                     .ml_2(),
                             ),
                     )
                     .child(
                         h_flex()
                             .my_2()
-                            .child("Current data was loaded from:")
+                            .child(t!("input.loaded_from").to_string())
                             .child(TextInput::new(&self.loaded_input).ml_2().disabled(true))
                             .child(
                                 Button::new("input-load")
                                     .on_click(cx.listener(|view, _, window, cx| {
-                                        view.update(window, cx, Command::LoadNewInputData);
+                                        view.dispatch(window, cx, Command::LoadNewInputData);
                                     }))
-                                    .child("Load new data")
+                                    .child(t!("input.load").to_string())
                                     .ml_2(),
                             ),
                     )
                     // Preview:
-                    .child(Label::new("Tabs as links:").my_2())
+                    .child(Label::new(t!("preview.label").to_string()).my_2())
                     .child(
                         TextInput::new(&self.preview)
                             .flex_grow()
                             .mb_2()
                             .disabled(true),
                     )
+                    .child(
+                        h_flex().my_2().child(
+                            Button::new("toggle-output-options")
+                                .icon(if self.show_output_options {
+                                    IconName::ChevronDown
+                                } else {
+                                    IconName::ChevronRight
+                                })
+                                .label(t!("output.toggle_options").to_string())
+                                .on_click(cx.listener(|view, _, window, cx| {
+                                    view.dispatch(window, cx, Command::ToggleOutputOptions);
+                                })),
+                        ),
+                    )
+                    .when(self.show_output_options, |this| {
+                        this.child(self.render_output_options(cx))
+                    })
                     // Output options:
                     .child(
                         h_flex()
                             .my_2()
-                            .child("File path to write links to:")
+                            .child(t!("output.path").to_string())
                             .child(TextInput::new(&self.output_path).ml_2())
                             .child(
                                 Button::new("output-browse")
-                                    .on_click(Self::output_browse_event_listener(window, cx))
-                                    .child("Browse")
+                                    .on_click(cx.listener(|view, _, window, cx| {
+                                        view.dispatch(window, cx, Command::BrowseSavePath);
+                                    }))
+                                    .child(t!("common.browse").to_string())
                                     .ml_2(),
                             ),
                     )
@@ -822,21 +4386,23 @@ impl Render for FirefoxSessionUtility {
                             .my_2()
                             .child(
                                 Checkbox::new("output-create-folder")
-                                    .label("Create folder if it doesn't exist")
+                                    .label(t!("output.create_folder").to_string())
                                     .checked(self.create_folder)
-                                    .on_click(cx.listener(|view, checked, _, cx| {
-                                        view.create_folder = *checked;
-                                        cx.notify();
+                                    .on_click(cx.listener(|view, checked, window, cx| {
+                                        view.dispatch(
+                                            window,
+                                            cx,
+                                            Command::SetCreateFolder(*checked),
+                                        );
                                     })),
                             )
                             .child(
                                 Checkbox::new("output-overwrite")
                                     .ml_4()
-                                    .label("Overwrite file if it already exists")
+                                    .label(t!("output.overwrite").to_string())
                                     .checked(self.overwrite)
-                                    .on_click(cx.listener(|view, checked, _, cx| {
-                                        view.overwrite = *checked;
-                                        cx.notify();
+                                    .on_click(cx.listener(|view, checked, window, cx| {
+                                        view.dispatch(window, cx, Command::SetOverwrite(*checked));
                                     })),
                             ),
                     )
@@ -850,12 +4416,25 @@ impl Render for FirefoxSessionUtility {
                             .child(
                                 v_flex().child(
                                     Button::new("copy-links-to-clipboard")
-                                        .on_click(cx.listener(|view, _, _window, cx| {
-                                            cx.write_to_clipboard(ClipboardItem::new_string(
-                                                view.preview.read(cx).value().as_str().to_owned(),
-                                            ));
+                                        .on_click(cx.listener(|view, _, window, cx| {
+                                            view.dispatch(
+                                                window,
+                                                cx,
+                                                Command::CopyLinksToClipboard,
+                                            );
+                                        }))
+                                        .child(t!("output.copy").to_string())
+                                        .flex_grow(),
+                                ),
+                            )
+                            .child(
+                                v_flex().child(
+                                    Button::new("open-tabs-in-browser")
+                                        .ml_2()
+                                        .on_click(cx.listener(|view, _, window, cx| {
+                                            view.dispatch(window, cx, Command::OpenSelectedTabs);
                                         }))
-                                        .child("Copy links to clipboard")
+                                        .child(t!("common.open_in_browser").to_string())
                                         .flex_grow(),
                                 ),
                             )
@@ -870,7 +4449,7 @@ impl Render for FirefoxSessionUtility {
                                         .child(
                                             v_flex()
                                                 .child(
-                                                    Label::new("Output format")
+                                                    Label::new(t!("output.format").to_string())
                                                         .text_center()
                                                         .mb_2(),
                                                 )
@@ -884,18 +4463,85 @@ impl Render for FirefoxSessionUtility {
                                                         .tooltip(Self::output_format_tooltip(
                                                             window, cx,
                                                         )),
+                                                )
+                                                .child(
+                                                    h_flex()
+                                                        .mt_2()
+                                                        .gap_2()
+                                                        .child(
+                                                            Button::new("new-template")
+                                                                .label(
+                                                                    t!("output.new_template")
+                                                                        .to_string(),
+                                                                )
+                                                                .on_click({
+                                                                    let view = self
+                                                                        .template_editor
+                                                                        .downgrade();
+                                                                    move |_, window, cx| {
+                                                                        TemplateEditor::open_modal(
+                                                                            window,
+                                                                            cx,
+                                                                            view.clone(),
+                                                                            None,
+                                                                        );
+                                                                    }
+                                                                }),
+                                                        )
+                                                        .child(
+                                                            Button::new("edit-template")
+                                                                .label(
+                                                                    t!("output.edit_template")
+                                                                        .to_string(),
+                                                                )
+                                                                .disabled(
+                                                                    selected_template.is_none(),
+                                                                )
+                                                                .on_click({
+                                                                    let view = self
+                                                                        .template_editor
+                                                                        .downgrade();
+                                                                    move |_, window, cx| {
+                                                                        TemplateEditor::open_modal(
+                                                                            window,
+                                                                            cx,
+                                                                            view.clone(),
+                                                                            selected_template
+                                                                                .clone(),
+                                                                        );
+                                                                    }
+                                                                }),
+                                                        ),
                                                 ),
                                         ),
                                 ),
                             )
+                            .child(
+                                v_flex().child(
+                                    Button::new("preview-pdf")
+                                        .ml_2()
+                                        .disabled(
+                                            !self
+                                                .selected_format
+                                                .as_ref()
+                                                .and_then(OutputFormat::as_builtin)
+                                                .is_some_and(host::is_pdf_format),
+                                        )
+                                        .on_click(cx.listener(|view, _, window, cx| {
+                                            view.dispatch(window, cx, Command::GeneratePdfPreview);
+                                        }))
+                                        .child(t!("output.preview_pdf").to_string())
+                                        .flex_grow(),
+                                ),
+                            )
                             .child(
                                 v_flex().child(
                                     Button::new("save-links-to-file")
                                         .ml_2()
                                         .on_click(cx.listener(|view, _, window, cx| {
-                                            view.update(window, cx, Command::SaveLinksToFile);
+                                            view.dispatch(window, cx, Command::SaveLinksToFile);
                                         }))
-                                        .child("Save links to file")
+                                        .child(t!("output.save").to_string())
                                         .flex_grow(),
                                 ),
                             ),
@@ -906,43 +4552,500 @@ impl Render for FirefoxSessionUtility {
                             .flex()
                             .my_2()
                             .flex_row()
-                            .child("Status:")
-                            .child(TextInput::new(&self.status).ml_2().disabled(true)),
-                    ),
+                            .child(t!("status.label").to_string())
+                            .child(
+                                h_flex()
+                                    .flex_1()
+                                    .ml_2()
+                                    .px_2()
+                                    .border_1()
+                                    .border_color(cx.theme().input)
+                                    .rounded(cx.theme().radius)
+                                    .overflow_hidden()
+                                    .text_color(cx.theme().muted_foreground)
+                                    .child(self.status.clone()),
+                            )
+                            .child({
+                                let errors = self.status_log.count(Severity::Error);
+                                Button::new("toggle-status-log")
+                                    .ml_2()
+                                    .icon(IconName::PanelBottom)
+                                    .when(errors > 0, |this| this.label(errors.to_string()))
+                                    .selected(self.show_status_log)
+                                    .tooltip(t!("status_log.toggle").to_string())
+                                    .on_click(cx.listener(|view, _, window, cx| {
+                                        view.dispatch(window, cx, Command::ToggleStatusLog);
+                                    }))
+                            })
+                            .child(
+                                Button::new("toggle-inspector")
+                                    .ml_2()
+                                    .icon(IconName::Inspector)
+                                    .selected(self.show_inspector)
+                                    .tooltip(t!("inspector.toggle").to_string())
+                                    .on_click(cx.listener(|view, _, window, cx| {
+                                        view.dispatch(window, cx, Command::ToggleInspector);
+                                    })),
+                            ),
+                    )
+                    .when(self.show_status_log, |this| {
+                        this.child(self.render_status_log(cx))
+                    })
+                    .when(self.show_inspector, |this| {
+                        this.child(self.render_inspector(cx))
+                    }),
             )
             // Render the modal layer on top of the app content
             .children(modal_layer)
+            .children(notification_layer)
+    }
+}
+
+/// Choices that can be undone.
+#[derive(Clone, PartialEq)]
+struct UndoState {
+    selection: host::GenerateOptions,
+    link_options: host::LinkOptions,
+    output_format: Option<OutputFormat>,
+    templates: Vec<Arc<template::OutputTemplate>>,
+    create_folder: bool,
+    overwrite: bool,
+}
+
+/// Sessions that are open as document tabs in a window.
+struct Workspace {
+    sessions: Vec<Entity<FirefoxSessionUtility>>,
+    active: usize,
+    _appearance: Subscription,
+    /// Observes each session, in the same order as `sessions`.
+    _subscriptions: Vec<Subscription>,
+}
+impl Workspace {
+    /// Open a session tab for each file path. If no paths are given then a
+    /// single session is opened, which loads the last used file if that is
+    /// enabled in the settings.
+    fn new(window: &mut Window, cx: &mut Context<Self>, input_paths: Vec<PathBuf>) -> Self {
+        let settings = settings::Settings::global(cx).clone();
+        if let Some((width, height)) = settings
+            .window_size
+            .filter(|_| settings.remember_window_size)
+        {
+            window.resize(Size::new(px(width), px(height)));
+        }
+        // Follow the system's light or dark mode while it is selected:
+        let appearance = cx.observe_window_appearance(window, |_, window, cx| {
+            if settings::Settings::global(cx).theme == settings::ThemePreference::System {
+                Theme::sync_system_appearance(Some(window), cx);
+            }
+        });
+        // Keep the window open the first time saving fails so that the error
+        // can be seen:
+        let workspace = cx.weak_entity();
+        let save_failed = Cell::new(false);
+        window.on_window_should_close(cx, move |window, cx| {
+            let size = window.viewport_size();
+            let settings = cx.global_mut::<settings::Settings>();
+            settings.window_size = Some((size.width.into(), size.height.into()));
+            let Err(e) = settings.save() else {
+                return true;
+            };
+            if save_failed.replace(true) {
+                return true;
+            }
+            let message = t!("status.save_settings_failed", error = e).to_string();
+            let hint = t!("notification.close_without_saving");
+            workspace
+                .update(cx, |workspace, cx| {
+                    workspace.report_settings_error(window, cx, message, Some(hint.as_ref()));
+                })
+                .is_err()
+        });
+
+        let mut input_paths = input_paths.into_iter().map(Some).collect::<Vec<_>>();
+        if input_paths.is_empty() {
+            input_paths.push(
+                settings
+                    .last_input_path
+                    .filter(|_| settings.load_last_input)
+                    .map(PathBuf::from),
+            );
+        }
+
+        let mut workspace = Self {
+            sessions: Vec::new(),
+            active: 0,
+            _appearance: appearance,
+            _subscriptions: Vec::new(),
+        };
+        for input_path in input_paths {
+            workspace.open_session(window, cx, input_path);
+        }
+        workspace.activate(window, cx, 0);
+        workspace
+    }
+
+    /// Show a problem with the settings file in the active session's status
+    /// bar and as a notification.
+    fn report_settings_error(
+        &self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+        message: String,
+        hint: Option<&str>,
+    ) {
+        if let Some(session) = self.sessions.get(self.active) {
+            session.update(cx, |session, cx| {
+                session.dispatch(
+                    window,
+                    cx,
+                    Command::SetStatus(Severity::Error, message.clone()),
+                );
+            });
+        }
+        let text = match hint {
+            Some(hint) => format!("{message}\n{hint}"),
+            None => message,
+        };
+        window.push_notification(
+            Notification::new()
+                .with_type(NotificationType::Error)
+                .message(text),
+            cx,
+        );
+    }
+
+    fn open_session(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+        input_path: Option<PathBuf>,
+    ) {
+        let session =
+            cx.new(|cx: &mut Context<'_, _>| FirefoxSessionUtility::new(window, cx, input_path));
+        // Update the tab's title when a file is loaded:
+        self._subscriptions
+            .push(cx.observe(&session, |_, _, cx| cx.notify()));
+        self.sessions.push(session);
+        self.activate(window, cx, self.sessions.len() - 1);
+    }
+
+    fn activate(&mut self, window: &mut Window, cx: &mut Context<Self>, index: usize) {
+        let Some(session) = self.sessions.get(index) else {
+            return;
+        };
+        self.active = index;
+        session.read(cx).focus_handle.focus(window);
+        cx.notify();
+    }
+
+    fn close_session(&mut self, window: &mut Window, cx: &mut Context<Self>, index: usize) {
+        if index >= self.sessions.len() {
+            return;
+        }
+        self.sessions.remove(index);
+        self._subscriptions.remove(index);
+        if self.sessions.is_empty() {
+            self.open_session(window, cx, None);
+        }
+        self.activate(window, cx, self.active.min(self.sessions.len() - 1));
+    }
+
+    fn render_tab_bar(&self, cx: &mut Context<'_, Self>) -> impl IntoElement {
+        h_flex()
+            .px_2()
+            .pt_1()
+            .gap_1()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .children(self.sessions.iter().enumerate().map(|(ix, session)| {
+                h_flex()
+                    .child(
+                        Button::new(("session-tab", ix))
+                            .label(session.read(cx).title(cx))
+                            .small()
+                            .ghost()
+                            .selected(ix == self.active)
+                            .on_click(cx.listener(move |workspace, _, window, cx| {
+                                workspace.activate(window, cx, ix);
+                            })),
+                    )
+                    .child(
+                        Button::new(("close-session-tab", ix))
+                            .icon(IconName::Close)
+                            .xsmall()
+                            .ghost()
+                            .tooltip(t!("session.close").to_string())
+                            .on_click(cx.listener(move |workspace, _, window, cx| {
+                                workspace.close_session(window, cx, ix);
+                            })),
+                    )
+            }))
+            .child(
+                Button::new("new-session-tab")
+                    .icon(IconName::Plus)
+                    .small()
+                    .ghost()
+                    .tooltip(t!("session.new").to_string())
+                    .on_click(cx.listener(|workspace, _, window, cx| {
+                        workspace.open_session(window, cx, None);
+                    })),
+            )
+            .child(div().flex_1())
+            .children(self.sessions.get(self.active).map(|session| {
+                let theme = settings::Settings::global(cx).theme;
+                Button::new("cycle-theme")
+                    .icon(theme_icon(theme))
+                    .small()
+                    .ghost()
+                    .tooltip(t!("session.theme", theme = theme.title()).to_string())
+                    .on_click(cx.listener(|workspace, _, window, cx| {
+                        if let Some(session) = workspace.sessions.get(workspace.active) {
+                            session.update(cx, |session, cx| {
+                                let mut settings = settings::Settings::global(cx).clone();
+                                settings.theme = settings.theme.next();
+                                session.dispatch(window, cx, Command::SetSettings(settings));
+                            });
+                        }
+                    }))
+            }))
+    }
+}
+impl Render for Workspace {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<'_, Self>) -> impl IntoElement {
+        v_flex()
+            .size_full()
+            .key_context(actions::WORKSPACE_CONTEXT)
+            .on_action(
+                cx.listener(|workspace, _: &actions::NewSessionTab, window, cx| {
+                    workspace.open_session(window, cx, None);
+                }),
+            )
+            .on_action(
+                cx.listener(|workspace, _: &actions::CloseSessionTab, window, cx| {
+                    workspace.close_session(window, cx, workspace.active);
+                }),
+            )
+            .on_action(
+                cx.listener(|workspace, _: &actions::NextSessionTab, window, cx| {
+                    let next = (workspace.active + 1) % workspace.sessions.len();
+                    workspace.activate(window, cx, next);
+                }),
+            )
+            .child(self.render_tab_bar(cx))
+            .children(self.sessions.get(self.active).cloned())
+    }
+}
+
+/// Icon shown for a theme choice.
+fn theme_icon(theme: settings::ThemePreference) -> IconName {
+    match theme {
+        settings::ThemePreference::System => IconName::Palette,
+        settings::ThemePreference::Light => IconName::Sun,
+        settings::ThemePreference::Dark => IconName::Moon,
+    }
+}
+
+/// Apply the theme and font size from the settings to every window.
+fn apply_appearance(settings: &settings::Settings, window: Option<&mut Window>, cx: &mut App) {
+    match settings.theme {
+        settings::ThemePreference::System => Theme::sync_system_appearance(window, cx),
+        settings::ThemePreference::Light => Theme::change(ThemeMode::Light, window, cx),
+        settings::ThemePreference::Dark => Theme::change(ThemeMode::Dark, window, cx),
     }
+    Theme::global_mut(cx).font_size = px(settings.font_size);
+    cx.refresh_windows();
+}
+
+/// Open a new main window with a session tab for each file path.
+/// `settings_error` is shown in the window if the settings couldn't be loaded.
+fn open_main_window(cx: &mut App, input_paths: Vec<PathBuf>, settings_error: Option<String>) {
+    cx.open_window(
+        WindowOptions {
+            titlebar: Some(gpui::TitlebarOptions {
+                title: Some(t!("app.title").to_string().into()),
+                ..Default::default()
+            }),
+            window_min_size: Some(Size::new(px(800.), px(600.))),
+            ..Default::default()
+        },
+        |window: &mut Window, cx: &mut App| {
+            apply_appearance(&settings::Settings::global(cx).clone(), Some(window), cx);
+
+            let main_ui = cx.new(|cx: &mut Context<'_, _>| Workspace::new(window, cx, input_paths));
+            if let Some(error) = settings_error {
+                let workspace = main_ui.downgrade();
+                // Notifications are shown by the root view, which doesn't
+                // exist until this returns:
+                window.defer(cx, move |window, cx| {
+                    let message = t!("status.load_settings_failed", error = error).to_string();
+                    let _ = workspace.update(cx, |workspace, cx| {
+                        workspace.report_settings_error(window, cx, message, None);
+                    });
+                });
+            }
+            cx.new(|cx| Root::new(main_ui.into(), window, cx))
+        },
+    )
+    .expect("Failed to build and open window");
 }
 
 fn main() {
     let rt = tokio::runtime::Runtime::new().expect("Failed to create Tokio runtime");
     let _rt_guard = rt.enter();
 
+    // Files to open, for example from a file association. Each file gets its
+    // own session tab:
+    let input_paths = std::env::args_os()
+        .skip(1)
+        .map(PathBuf::from)
+        .collect::<Vec<_>>();
+
     Application::new().with_assets(Assets).run(|cx: &mut App| {
         cx.new(|cx: &mut Context<'_, ()>| {
             // This must be called before using any GPUI Component features.
             gpui_component::init(cx);
+            let settings_error = settings::Settings::init(cx).err();
+            locale::set_language(settings::Settings::global(cx).language.as_deref());
+            actions::bind_keys(cx);
 
-            cx.open_window(
-                WindowOptions {
-                    titlebar: Some(gpui::TitlebarOptions {
-                        title: Some("Firefox Session Data Utility".into()),
-                        ..Default::default()
-                    }),
-                    window_min_size: Some(Size::new(px(800.), px(600.))),
-                    ..Default::default()
-                },
-                |window: &mut Window, cx: &mut App| {
-                    // Uncomment next line to test a specific theme instead of using the system theme:
-                    // gpui_component::Theme::change(gpui_component::ThemeMode::Light, Some(window), cx);
-
-                    let main_ui =
-                        cx.new(|cx: &mut Context<'_, _>| FirefoxSessionUtility::new(window, cx));
-                    cx.new(|cx| Root::new(main_ui.into(), window, cx))
-                },
-            )
-            .expect("Failed to build and open window");
+            open_main_window(cx, input_paths, settings_error);
         });
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use gpui::{TestAppContext, VisualTestContext};
+
+    use super::*;
+
+    fn new_session(
+        cx: &mut TestAppContext,
+    ) -> (Entity<FirefoxSessionUtility>, &mut VisualTestContext) {
+        cx.update(|cx| {
+            gpui_component::init(cx);
+            cx.set_global(settings::Settings::default());
+        });
+        cx.add_window_view(|window, cx| FirefoxSessionUtility::new(window, cx, None))
+    }
+
+    /// Handle a command without running the effects that it returns.
+    fn update(
+        view: &Entity<FirefoxSessionUtility>,
+        cx: &mut VisualTestContext,
+        command: Command,
+    ) -> Effect<Command> {
+        view.update(cx, |view, cx| Update::update(view, cx, command))
+    }
+
+    fn saves_settings_later(effect: &Effect<Command>) -> bool {
+        match effect {
+            Effect::SendLater {
+                id: Some(SAVE_SETTINGS_TASK),
+                delay,
+                msg: Command::SaveSettings,
+            } => *delay == SAVE_SETTINGS_DELAY,
+            Effect::Batch(effects) => effects.iter().any(saves_settings_later),
+            _ => false,
+        }
+    }
+
+    #[gpui::test]
+    fn set_create_folder_updates_settings_and_can_be_undone(cx: &mut TestAppContext) {
+        let (view, cx) = new_session(cx);
+
+        let effect = update(&view, cx, Command::SetCreateFolder(true));
+        assert!(saves_settings_later(&effect));
+        assert!(view.read_with(cx, |view, _cx| view.create_folder));
+        assert!(cx.read(|cx| settings::Settings::global(cx).create_folder));
+
+        let effect = update(&view, cx, Command::Undo);
+        assert!(saves_settings_later(&effect));
+        assert!(!view.read_with(cx, |view, _cx| view.create_folder));
+        assert!(!cx.read(|cx| settings::Settings::global(cx).create_folder));
+    }
+
+    #[gpui::test]
+    fn undo_restores_custom_page_break_input(cx: &mut TestAppContext) {
+        let (view, cx) = new_session(cx);
+        let dispatch = |cx: &mut VisualTestContext, command: Command| {
+            view.update_in(cx, |view, window, cx| view.dispatch(window, cx, command));
+            cx.run_until_parked();
+        };
+        let page_break = |cx: &mut VisualTestContext| {
+            view.read_with(cx, |view, cx| {
+                view.custom_page_break.read(cx).value().to_string()
+            })
+        };
+
+        let mut link_options = view.read_with(cx, |view, _cx| view.link_options.clone());
+        link_options.custom_page_break = "---".to_owned();
+        dispatch(cx, Command::SetLinkOptions(link_options));
+        dispatch(cx, Command::Undo);
+        assert_eq!(page_break(cx), "");
+
+        dispatch(cx, Command::Redo);
+        assert_eq!(page_break(cx), "---");
+        assert_eq!(
+            view.read_with(cx, |view, _cx| view.link_options.custom_page_break.clone()),
+            "---"
+        );
+    }
+
+    #[gpui::test]
+    fn unchanged_settings_are_not_saved(cx: &mut TestAppContext) {
+        let (view, cx) = new_session(cx);
+        let settings = cx.read(|cx| settings::Settings::global(cx).clone());
+
+        let effect = update(&view, cx, Command::SetSettings(settings));
+        assert!(matches!(effect, Effect::None));
+    }
+
+    #[gpui::test]
+    fn set_status_is_logged(cx: &mut TestAppContext) {
+        let (view, cx) = new_session(cx);
+
+        let effect = update(
+            &view,
+            cx,
+            Command::SetStatus(Severity::Warning, "Careful".to_owned()),
+        );
+        assert!(matches!(effect, Effect::None));
+        view.read_with(cx, |view, _cx| {
+            assert_eq!(view.status.as_ref(), "Careful");
+            let last = view.status_log.entries().back().unwrap();
+            assert_eq!(
+                (last.severity, last.message.as_str()),
+                (Severity::Warning, "Careful")
+            );
+        });
+
+        let _ = update(&view, cx, Command::ClearStatusLog);
+        assert!(view.read_with(cx, |view, _cx| view.status_log.entries().is_empty()));
+    }
+
+    #[gpui::test]
+    fn toggle_status_log(cx: &mut TestAppContext) {
+        let (view, cx) = new_session(cx);
+        let shown = view.read_with(cx, |view, _cx| view.show_status_log);
+
+        let _ = update(&view, cx, Command::ToggleStatusLog);
+        assert_eq!(view.read_with(cx, |view, _cx| view.show_status_log), !shown);
+        let _ = update(&view, cx, Command::ToggleStatusLog);
+        assert_eq!(view.read_with(cx, |view, _cx| view.show_status_log), shown);
+    }
+
+    #[gpui::test]
+    fn report_error_sets_status_and_leaves_notification_to_window(cx: &mut TestAppContext) {
+        let (view, cx) = new_session(cx);
+        let report = ErrorReport::new(FailedTask::SaveLinks, "links.pdf", "disk full");
+        let summary = report.summary();
+
+        let effect = update(&view, cx, Command::ReportError(report));
+        assert!(matches!(effect, Effect::Window(_)));
+        view.read_with(cx, |view, _cx| {
+            assert_eq!(view.status.as_ref(), summary);
+            assert_eq!(view.status_log.count(Severity::Error), 1);
+        });
+    }
+}
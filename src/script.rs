@@ -0,0 +1,213 @@
+//! Output formats that write scripts which reopen windows in Firefox.
+
+use rust_i18n::t;
+use serde::{Deserialize, Serialize};
+
+use crate::host::{AllTabGroups, GenerateOptions};
+
+/// A kind of script that can reopen windows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScriptKind {
+    /// POSIX shell script.
+    Posix,
+    PowerShell,
+}
+impl ScriptKind {
+    pub fn all() -> &'static [Self] {
+        &[Self::Posix, Self::PowerShell]
+    }
+    pub fn title(&self) -> String {
+        match self {
+            Self::Posix => t!("script.posix"),
+            Self::PowerShell => t!("script.powershell"),
+        }
+        .into_owned()
+    }
+    /// Markdown description of the script.
+    pub fn description(&self) -> String {
+        match self {
+            Self::Posix => t!("script.posix_description"),
+            Self::PowerShell => t!("script.powershell_description"),
+        }
+        .into_owned()
+    }
+    pub fn file_extension(&self) -> &'static str {
+        match self {
+            Self::Posix => "sh",
+            Self::PowerShell => "ps1",
+        }
+    }
+
+    /// Write a script that opens each selected window. If `profile` isn't
+    /// empty then the windows are opened in that Firefox profile.
+    pub fn render(
+        &self,
+        tab_groups: &AllTabGroups,
+        options: &GenerateOptions,
+        profile: &str,
+    ) -> String {
+        let quote = match self {
+            Self::Posix => quote_posix,
+            Self::PowerShell => quote_powershell,
+        };
+        let mut output = match self {
+            Self::Posix => concat!(
+                "#!/bin/sh\n",
+                "# Reopens windows from a Firefox session.\n",
+                "# Set the FIREFOX environment variable to use another executable.\n",
+                "FIREFOX=\"${FIREFOX:-firefox}\"\n",
+            )
+            .to_owned(),
+            Self::PowerShell => concat!(
+                "# Reopens windows from a Firefox session.\n",
+                "# Change this to the full path of firefox.exe if it isn't in the PATH.\n",
+                "$firefox = 'firefox'\n",
+            )
+            .to_owned(),
+        };
+        let command = match self {
+            Self::Posix => "\"$FIREFOX\"",
+            Self::PowerShell => "& $firefox",
+        };
+        // The first launch keeps running as the browser, so don't wait for it:
+        let line_end = match self {
+            Self::Posix => " &\n",
+            Self::PowerShell => "\n",
+        };
+        let profile = if profile.trim().is_empty() {
+            String::new()
+        } else {
+            format!(" -P {}", quote(profile.trim()))
+        };
+
+        for group in tab_groups.selected_groups(options) {
+            if group.tabs.is_empty() {
+                continue;
+            }
+            // Keep the window name on a single comment line:
+            let name = group.name.replace(['\r', '\n'], " ");
+            output.push_str(&format!("\n# {name}\n{command}{profile} --new-window"));
+            for tab in &group.tabs {
+                output.push(' ');
+                output.push_str(&quote(&tab.url));
+            }
+            output.push_str(line_end);
+        }
+        output
+    }
+}
+
+/// Quote an argument for a POSIX shell.
+fn quote_posix(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Quote an argument for PowerShell. Typographic single quotes end a quoted
+/// string just like `'` does, so they are escaped by doubling them as well.
+fn quote_powershell(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('\'');
+    for c in value.chars() {
+        if matches!(c, '\'' | '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}') {
+            quoted.push(c);
+        }
+        quoted.push(c);
+    }
+    quoted.push('\'');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::{TabGroup, TabInfo};
+
+    fn tab_groups() -> AllTabGroups {
+        let group = |index: u32, name: &str, urls: &[&str]| TabGroup {
+            index,
+            name: name.to_owned(),
+            tabs: urls
+                .iter()
+                .zip(0..)
+                .map(|(url, index)| TabInfo {
+                    index,
+                    title: String::new(),
+                    url: (*url).to_owned(),
+                    parent: None,
+                    last_accessed: None,
+                    pinned: false,
+                })
+                .collect(),
+            closed_at: None,
+        };
+        AllTabGroups {
+            open: vec![
+                group(0, "Window 1", &["https://a.com", "https://b.com"]),
+                group(1, "Window 2", &["https://c.com/it's"]),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn posix_launches_each_window_in_background() {
+        let script = ScriptKind::Posix.render(&tab_groups(), &GenerateOptions::default(), "");
+        let lines = script
+            .lines()
+            .filter(|line| line.starts_with("\"$FIREFOX\""))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            [
+                "\"$FIREFOX\" --new-window 'https://a.com' 'https://b.com' &",
+                r#""$FIREFOX" --new-window 'https://c.com/it'\''s' &"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn powershell_passes_profile() {
+        let script =
+            ScriptKind::PowerShell.render(&tab_groups(), &GenerateOptions::default(), "work");
+        assert!(script.contains(concat!(
+            "\n# Window 1\n",
+            "& $firefox -P 'work' --new-window 'https://a.com' 'https://b.com'\n",
+        )));
+    }
+
+    #[test]
+    fn posix_quotes_special_characters() {
+        assert_eq!(
+            quote_posix("https://example.com/?q=a b"),
+            "'https://example.com/?q=a b'"
+        );
+        assert_eq!(
+            quote_posix("https://example.com/?q=$HOME`id`"),
+            "'https://example.com/?q=$HOME`id`'"
+        );
+        assert_eq!(
+            quote_posix("https://example.com/it's"),
+            r"'https://example.com/it'\''s'"
+        );
+    }
+
+    #[test]
+    fn powershell_quotes_special_characters() {
+        assert_eq!(
+            quote_powershell("https://example.com/?q=a b"),
+            "'https://example.com/?q=a b'"
+        );
+        assert_eq!(
+            quote_powershell("https://example.com/?q=$env:PATH`n"),
+            "'https://example.com/?q=$env:PATH`n'"
+        );
+        assert_eq!(
+            quote_powershell("https://example.com/it's"),
+            "'https://example.com/it''s'"
+        );
+        assert_eq!(
+            quote_powershell("https://example.com/\u{2018}a\u{2019}"),
+            "'https://example.com/\u{2018}\u{2018}a\u{2019}\u{2019}'"
+        );
+    }
+}
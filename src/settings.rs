@@ -0,0 +1,214 @@
+//! Application settings that are remembered across restarts.
+
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use gpui::{App, Global};
+use rust_i18n::t;
+use serde::{Deserialize, Serialize};
+
+use crate::template::OutputTemplate;
+
+/// Name of the folder inside the platform's config directory where settings
+/// are stored.
+const APP_FOLDER: &str = "firefox-session-ui-gpui";
+
+/// Max number of files that are remembered in the recent files list.
+const MAX_RECENT_FILES: usize = 10;
+
+/// Font size used when no other size has been chosen.
+pub const DEFAULT_FONT_SIZE: f32 = 16.;
+/// Smallest and largest font sizes that can be chosen.
+pub const FONT_SIZE_RANGE: (f32, f32) = (10., 28.);
+
+/// Color theme of the user interface.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ThemePreference {
+    /// Follow the light or dark mode of the operating system.
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+impl ThemePreference {
+    pub fn all() -> &'static [Self] {
+        &[Self::System, Self::Light, Self::Dark]
+    }
+    pub fn title(&self) -> String {
+        match self {
+            Self::System => t!("theme.system"),
+            Self::Light => t!("theme.light"),
+            Self::Dark => t!("theme.dark"),
+        }
+        .into_owned()
+    }
+    /// The theme that follows this one when cycling through them.
+    pub fn next(&self) -> Self {
+        match self {
+            Self::System => Self::Light,
+            Self::Light => Self::Dark,
+            Self::Dark => Self::System,
+        }
+    }
+}
+
+/// A sessionstore file that was loaded previously.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecentFile {
+    pub path: String,
+    /// Number of open windows the last time the file was loaded.
+    pub window_count: usize,
+}
+impl RecentFile {
+    /// Name of the Firefox profile folder that the file is stored in, if any.
+    pub fn profile(&self) -> Option<String> {
+        let path = Path::new(&self.path);
+        let folder = path.parent()?;
+        let profile = if folder.file_name()? == "sessionstore-backups" {
+            folder.parent()?
+        } else if path.file_name()? == "sessionstore.jsonlz4" {
+            folder
+        } else {
+            return None;
+        };
+        Some(profile.file_name()?.to_string_lossy().into_owned())
+    }
+    /// When the file was last modified.
+    pub fn modified(&self) -> Option<SystemTime> {
+        std::fs::metadata(&self.path).ok()?.modified().ok()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// File path that links are written to.
+    pub output_path: String,
+    pub create_folder: bool,
+    pub overwrite: bool,
+    /// Title of the selected output format.
+    pub output_format: Option<String>,
+    /// User defined output formats.
+    pub templates: Vec<OutputTemplate>,
+    /// Path of the most recently loaded sessionstore file.
+    pub last_input_path: Option<String>,
+    /// Most recently loaded files first.
+    pub recent_files: Vec<RecentFile>,
+    /// Load the most recently loaded sessionstore file at startup.
+    pub load_last_input: bool,
+    pub remember_window_size: bool,
+    /// Reload the loaded file when another program such as Firefox changes it.
+    pub live_reload: bool,
+    /// Command used to open tabs in a browser. The system's default browser
+    /// is used if this is empty.
+    pub browser_command: String,
+    /// Ask before opening more than this many tabs in a browser.
+    pub open_tabs_confirm_limit: usize,
+    /// Firefox profile that generated scripts open windows in.
+    pub script_profile: String,
+    /// Width and height of the window's content area.
+    pub window_size: Option<(f32, f32)>,
+    pub theme: ThemePreference,
+    /// Base font size in pixels.
+    pub font_size: f32,
+    /// Locale code of the user interface language. The system language is
+    /// used if this is `None`.
+    pub language: Option<String>,
+}
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            output_path: dirs::download_dir()
+                .map(|folder| folder.join("firefox-links").to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            create_folder: false,
+            overwrite: false,
+            output_format: None,
+            templates: Vec::new(),
+            last_input_path: None,
+            recent_files: Vec::new(),
+            load_last_input: false,
+            remember_window_size: true,
+            live_reload: false,
+            browser_command: String::new(),
+            open_tabs_confirm_limit: 20,
+            script_profile: String::new(),
+            window_size: None,
+            theme: ThemePreference::System,
+            font_size: DEFAULT_FONT_SIZE,
+            language: None,
+        }
+    }
+}
+/// The settings are shared by all windows and session tabs so that a change in
+/// one of them is never overwritten by an outdated copy in another.
+impl Global for Settings {}
+impl Settings {
+    /// Load the settings file into the shared settings. Default settings are
+    /// used if it can't be loaded, and the error is returned so that it can be
+    /// shown once a window is open.
+    pub fn init(cx: &mut App) -> Result<(), String> {
+        let (settings, result) = match Self::load() {
+            Ok(settings) => (settings, Ok(())),
+            Err(e) => (Self::default(), Err(e)),
+        };
+        cx.set_global(settings);
+        result
+    }
+
+    /// Settings shared by all windows and session tabs.
+    pub fn global(cx: &App) -> &Self {
+        cx.global::<Self>()
+    }
+
+    /// Location of the settings file.
+    pub fn path() -> Option<PathBuf> {
+        Some(dirs::config_dir()?.join(APP_FOLDER).join("settings.json"))
+    }
+
+    /// Load settings from the settings file. Default settings are returned if
+    /// the file doesn't exist yet.
+    pub fn load() -> Result<Self, String> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        let data = match std::fs::read(&path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => {
+                return Err(t!("error.read_settings", path = path.display(), error = e).into_owned())
+            }
+        };
+        serde_json::from_slice(&data)
+            .map_err(|e| t!("error.parse_settings", path = path.display(), error = e).into_owned())
+    }
+
+    /// Change the font size by `delta` pixels, staying within
+    /// [`FONT_SIZE_RANGE`].
+    pub fn change_font_size(&mut self, delta: f32) {
+        let (min, max) = FONT_SIZE_RANGE;
+        self.font_size = (self.font_size + delta).clamp(min, max);
+    }
+
+    /// Move a file to the start of the recent files list.
+    pub fn add_recent_file(&mut self, path: String, window_count: usize) {
+        self.recent_files.retain(|file| file.path != path);
+        self.recent_files
+            .insert(0, RecentFile { path, window_count });
+        self.recent_files.truncate(MAX_RECENT_FILES);
+    }
+
+    /// Write settings to the settings file.
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::path().ok_or_else(|| t!("error.no_config_folder").into_owned())?;
+        if let Some(folder) = path.parent() {
+            std::fs::create_dir_all(folder).map_err(|e| {
+                t!("error.create_folder", path = folder.display(), error = e).into_owned()
+            })?;
+        }
+        let data = serde_json::to_vec_pretty(self)
+            .map_err(|e| t!("error.serialize_settings", error = e).into_owned())?;
+        std::fs::write(&path, data)
+            .map_err(|e| t!("error.write_settings", path = path.display(), error = e).into_owned())
+    }
+}
@@ -0,0 +1,109 @@
+//! History of status messages so that earlier errors can be reviewed after
+//! newer messages have replaced them in the status bar.
+
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Local};
+use rust_i18n::t;
+
+/// Max number of entries that are kept. Older entries are discarded first.
+const MAX_ENTRIES: usize = 1000;
+
+/// How important a status message is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Progress of a task.
+    Info,
+    /// A task finished.
+    Success,
+    /// Nothing failed, but the action had no effect.
+    Warning,
+    Error,
+}
+impl Severity {
+    pub fn title(&self) -> String {
+        match self {
+            Self::Info => t!("status_log.info"),
+            Self::Success => t!("status_log.success"),
+            Self::Warning => t!("status_log.warning"),
+            Self::Error => t!("status_log.error"),
+        }
+        .into_owned()
+    }
+    /// Untranslated name used in exported logs, so that bug reports can be
+    /// read by everyone.
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Info => "INFO",
+            Self::Success => "SUCCESS",
+            Self::Warning => "WARNING",
+            Self::Error => "ERROR",
+        }
+    }
+}
+
+/// A status message and when it was shown.
+#[derive(Debug, Clone)]
+pub struct StatusEntry {
+    pub time: DateTime<Local>,
+    pub severity: Severity,
+    pub message: String,
+}
+impl StatusEntry {
+    /// Time of day shown in the log panel.
+    pub fn short_time(&self) -> String {
+        self.time.format("%H:%M:%S").to_string()
+    }
+}
+
+/// Status messages in the order they were shown.
+#[derive(Debug, Clone, Default)]
+pub struct StatusLog {
+    entries: VecDeque<StatusEntry>,
+}
+impl StatusLog {
+    pub fn push(&mut self, severity: Severity, message: String) {
+        if self.entries.len() >= MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(StatusEntry {
+            time: Local::now(),
+            severity,
+            message,
+        });
+    }
+    /// Oldest entries first.
+    pub fn entries(&self) -> &VecDeque<StatusEntry> {
+        &self.entries
+    }
+    /// Number of entries with the specified severity.
+    pub fn count(&self, severity: Severity) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| entry.severity == severity)
+            .count()
+    }
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Write all entries as plain text with one entry per line, suitable for
+    /// attaching to bug reports.
+    pub fn export(&self) -> String {
+        let mut output = format!(
+            "{} {} status log\n",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION")
+        );
+        for entry in &self.entries {
+            output.push_str(&format!(
+                "{} [{}] {}\n",
+                entry.time.format("%Y-%m-%d %H:%M:%S%.3f %:z"),
+                entry.severity.as_str(),
+                // Keep each entry on a single line:
+                entry.message.replace(['\r', '\n'], " "),
+            ));
+        }
+        output
+    }
+}
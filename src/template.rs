@@ -0,0 +1,151 @@
+//! User defined output formats where links are written using small text
+//! templates.
+
+use serde::{Deserialize, Serialize};
+
+use crate::host::{self, AllTabGroups, GenerateOptions, TabInfo};
+
+/// A named output format that is defined by text templates.
+///
+/// Each part is followed by a new line when it is written. The following
+/// placeholders are supported:
+///
+/// - `{group}`: name of the current tab group.
+/// - `{index}`: 1-based index of the current tab group or tab.
+/// - `{title}`: title of the current tab.
+/// - `{url}`: URL of the current tab.
+/// - `{last_accessed}`: when the current tab was last accessed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutputTemplate {
+    /// Name shown in the output format dropdown.
+    pub name: String,
+    /// File extension used when the saved file path doesn't have one.
+    pub file_extension: String,
+    /// Written once before everything else.
+    pub header: String,
+    /// Written before the tabs of each group.
+    pub group: String,
+    /// Written once for each tab.
+    pub tab: String,
+    /// Written once after everything else.
+    pub footer: String,
+}
+impl Default for OutputTemplate {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            file_extension: "txt".to_owned(),
+            header: String::new(),
+            group: "# {group}".to_owned(),
+            tab: "- [{title}]({url})".to_owned(),
+            footer: String::new(),
+        }
+    }
+}
+impl OutputTemplate {
+    /// Write the selected tab groups using this template.
+    pub fn render(&self, tab_groups: &AllTabGroups, options: &GenerateOptions) -> String {
+        let mut output = String::new();
+        let mut write_part = |text: String| {
+            if !text.is_empty() {
+                output.push_str(&text);
+                output.push('\n');
+            }
+        };
+
+        write_part(fill_placeholders(&self.header, |_| None));
+        for (index, group) in tab_groups.selected_groups(options).enumerate() {
+            write_part(fill_placeholders(&self.group, |name| match name {
+                "group" => Some(group.name.clone()),
+                "index" => Some((index + 1).to_string()),
+                _ => None,
+            }));
+            for (index, tab) in group.tabs.iter().enumerate() {
+                write_part(fill_placeholders(&self.tab, |name| match name {
+                    "group" => Some(group.name.clone()),
+                    "index" => Some((index + 1).to_string()),
+                    "title" => Some(tab.title.clone()),
+                    "url" => Some(tab.url.clone()),
+                    "last_accessed" => Some(format_last_accessed(tab)),
+                    _ => None,
+                }));
+            }
+        }
+        write_part(fill_placeholders(&self.footer, |_| None));
+
+        output
+    }
+}
+
+fn format_last_accessed(tab: &TabInfo) -> String {
+    tab.last_accessed
+        .and_then(host::format_timestamp)
+        .unwrap_or_default()
+}
+
+/// Replace `{name}` placeholders in a template. Unknown placeholders and
+/// braces that don't form a placeholder are left as they are.
+fn fill_placeholders(template: &str, mut lookup: impl FnMut(&str) -> Option<String>) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find('}') else {
+            break;
+        };
+        // Only the innermost `{` starts the placeholder:
+        if let Some(inner) = rest[1..end].rfind('{') {
+            output.push_str(&rest[..=inner]);
+            rest = &rest[inner + 1..];
+            continue;
+        }
+        match lookup(&rest[1..end]) {
+            Some(value) => output.push_str(&value),
+            None => output.push_str(&rest[..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+    output.push_str(rest);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(template: &str) -> String {
+        fill_placeholders(template, |name| match name {
+            "title" => Some("Example".to_owned()),
+            "url" => Some("https://example.com".to_owned()),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn fills_known_placeholders() {
+        assert_eq!(
+            fill("- [{title}]({url})"),
+            "- [Example](https://example.com)"
+        );
+    }
+
+    #[test]
+    fn keeps_unknown_placeholders() {
+        assert_eq!(fill("{group}: {title}"), "{group}: Example");
+        assert_eq!(fill("{}"), "{}");
+    }
+
+    #[test]
+    fn keeps_unclosed_brace() {
+        assert_eq!(fill("{title} {url"), "Example {url");
+        assert_eq!(fill("{"), "{");
+    }
+
+    #[test]
+    fn fills_innermost_placeholder() {
+        assert_eq!(fill("{{title}}"), "{Example}");
+        assert_eq!(fill("{a {title}"), "{a Example");
+    }
+}